@@ -47,6 +47,11 @@ fn bench<M: Measurement, const T: u8>(c: &mut Criterion<M>) {
     crate::top::add_top_benchmark::<_, ITERATIONS>(c);
 }
 
+fn latency(c: &mut Criterion) {
+    #[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
+    crate::specific::avx2::add_latency_benchmarks(c);
+}
+
 #[non_exhaustive]
 struct Type;
 
@@ -71,4 +76,9 @@ criterion_group!(
 //     config = Criterion::default().with_measurement(Perf::new(Builder::from_hardware_event(Hardware::RefCPUCycles)));
 //     targets = bench::<_, { Type::CYCLES }>
 // );
-criterion_main!(time);
+criterion_group!(
+    name = latency_group;
+    config = Criterion::default();
+    targets = latency
+);
+criterion_main!(time, latency_group);
@@ -1,10 +1,16 @@
-use std::{arch::x86_64::*, mem};
+use std::{
+    arch::x86_64::*,
+    mem,
+    time::{Duration, Instant},
+};
 
 use criterion::{black_box, measurement::Measurement, BenchmarkId, Criterion, Throughput};
 use rand_core::SeedableRng;
 use simd_rand::specific::avx2::*;
 
 type Shishua = simd_rand::specific::avx2::Shishua<DEFAULT_BUFFER_SIZE>;
+type BackgroundShishua = simd_rand::specific::avx2::BackgroundShishua<DEFAULT_BUFFER_SIZE>;
+type ShishuaIncremental = simd_rand::specific::avx2::ShishuaIncremental<DEFAULT_BUFFER_SIZE>;
 
 pub fn add_benchmarks<M: Measurement, const ITERATIONS: usize>(c: &mut Criterion<M>, suffix: &str) {
     let group_prefix = "AVX2";
@@ -105,3 +111,50 @@ fn add_m256d_benchmarks<M: Measurement, const ITERATIONS: usize>(
 
     group.finish();
 }
+
+/// Reports the maximum latency of a batch of `next_m256i` calls (instead of the average),
+/// observed over enough batches to span several rebuffers.
+/// Timing single calls would mostly measure the timer itself, a batch is small enough
+/// that a rebuffer still stands out from the batches served straight from the buffer.
+/// Only makes sense for wall time, so it's in its own group.
+pub fn add_latency_benchmarks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("AVX2/m256i max latency");
+
+    const BUFFERS: usize = 8;
+    const BATCH: usize = 16;
+    const BATCHES: usize = (DEFAULT_BUFFER_SIZE / mem::size_of::<__m256i>()) * BUFFERS / BATCH;
+
+    // Each iteration reports the slowest batch out of BATCHES batches
+    fn execute<RNG: SimdRand>(rng: &mut RNG, iters: u64) -> Duration {
+        let mut total = Duration::ZERO;
+        for _ in 0..iters {
+            let mut max = Duration::ZERO;
+            for _ in 0..BATCHES {
+                let start = Instant::now();
+                for _ in 0..BATCH {
+                    black_box(rng.next_m256i());
+                }
+                max = max.max(start.elapsed());
+            }
+            total += max;
+        }
+        total
+    }
+
+    group.bench_function("Shishua", |b| {
+        let mut rng = Shishua::seed_from_u64(0x0DDB1A5E5BAD5EEDu64);
+        b.iter_custom(|iters| execute(&mut rng, iters))
+    });
+
+    group.bench_function("ShishuaIncremental", |b| {
+        let mut rng = ShishuaIncremental::seed_from_u64(0x0DDB1A5E5BAD5EEDu64);
+        b.iter_custom(|iters| execute(&mut rng, iters))
+    });
+
+    group.bench_function("BackgroundShishua", |b| {
+        let mut rng = BackgroundShishua::seed_from_u64(0x0DDB1A5E5BAD5EEDu64);
+        b.iter_custom(|iters| execute(&mut rng, iters))
+    });
+
+    group.finish();
+}
@@ -15,7 +15,7 @@ use std::mem;
 #[repr(align(64))]
 struct Buf([u64; 512]);
 
-type RngBaseline = Xoshiro256PlusPlus;
+type RngBaseline = rand_xoshiro::Xoshiro256PlusPlus;
 type RngVecSeed = Xoshiro256PlusPlusX8Seed;
 type RngVecImpl = Xoshiro256PlusPlusX8;
 
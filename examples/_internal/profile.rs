@@ -1,12 +1,10 @@
 use std::{hint::black_box, time::Instant};
 
-use rand_core::{RngCore, SeedableRng};
 use simd_rand::portable::*;
+use simd_rand::seeding::FromOsEntropy;
 
 fn main() {
-    let mut seed: Xoshiro256PlusPlusX8Seed = Default::default();
-    rand::thread_rng().fill_bytes(&mut *seed);
-    let mut rng = Xoshiro256PlusPlusX8::from_seed(seed);
+    let mut rng = Xoshiro256PlusPlusX8::from_os_entropy();
 
     for _ in 0..4 {
         let start = Instant::now();
@@ -3,11 +3,15 @@
 //! Provides SIMD implementations of common PRNGs in Rust.
 //! Categories:
 //! - [`portable`] - portable implementations using `std::simd` (nightly required)
+//!   - generic over the lane count, e.g. `Xoshiro256PlusPlusX<2>` or `Xoshiro256PlusPlusX<16>`, with `X4`/`X8` aliases for the common widths
 //! - [`specific`] - implementations using architecture-specific hardware intrinsics
 //!   - [`specific::avx2`] - AVX2 for x86_64 architecture (4 lanes for 64bit)
 //!     - Requires `avx2` CPU flag, but has additional optimization if you have `avx512dq` and `avx512vl`
 //!   - [`specific::avx512`] - AVX512 for x86_64 architecture (8 lanes for 64bit)
 //!     - Requires `avx512f`, `avx512dq` CPU flags
+//!   - [`specific::rdrand`] - seeding from RDSEED/RDRAND for x86_64 architecture, with OS entropy as fallback
+//! - [`seeding`] - seed derivation shared by all generators, e.g. from string keys, OS entropy or NumPy compatible seed sequences
+//! - [`xoshiro`] - helpers shared by the xoshiro generators, e.g. for skipping ahead
 //!
 //! Vectorized PRNG implementations may perform anywhere from 4-6 times faster in my experience,
 //! of course very dependent on hardware used ("old" CPUs with AVX512 for example may have excessive thermal throttling).
@@ -47,6 +51,9 @@
 //!
 //! The `specific` submodules (AVX2 and AVX512 currently) are only compiled in depending on target arch/features.
 //!
+//! The xoshiro generators can be built from and split into the scalar `rand_xoshiro` generators of their lanes
+//! with `from_lanes`/`into_lanes` when the `rand_xoshiro` feature is enabled, `from_lane_states`/`lane_states` work on the raw states.
+//!
 //! In general, use the `portable` module. The only risk/drawback to using the `portable` module is that in principle
 //! the compiler isn't _forced_ to use the "optimal" instructions and registers for your hardware. In practice, it probably will though.
 //! In the `specific` submodules the respective hardware intrinsics are "hardcoded" so to speak so we always know what the generated code looks like.
@@ -119,9 +126,12 @@
     feature(stdarch_x86_avx512)
 )]
 #![feature(portable_simd)]
+#![feature(allocator_api)]
 
 pub mod portable;
+pub mod seeding;
 pub mod specific;
+pub mod xoshiro;
 
 #[cfg(test)]
 mod testutil;
@@ -6,16 +6,12 @@ use std::{
 };
 
 pub use simdrand::*;
-pub use xoshiro256plusplusx4::*;
-pub use xoshiro256plusplusx8::*;
-pub use xoshiro256plusx4::*;
-pub use xoshiro256plusx8::*;
+pub use xoshiro256plus::*;
+pub use xoshiro256plusplus::*;
 
 mod simdrand;
-mod xoshiro256plusplusx4;
-mod xoshiro256plusplusx8;
-mod xoshiro256plusx4;
-mod xoshiro256plusx8;
+mod xoshiro256plus;
+mod xoshiro256plusplus;
 
 #[inline(always)]
 fn read_u64_into_vec<const N: usize>(src: &[u8]) -> Simd<u64, N>
@@ -34,6 +30,13 @@ where
     Simd::<u64, N>::from_array(scalars)
 }
 
+/// Writes lanes into seed bytes, the inverse of [`read_u64_into_vec`].
+fn write_u64_lanes_into_seed<const N: usize>(lanes: [u64; N], dst: &mut [u8]) {
+    for (chunk, lane) in dst.chunks_exact_mut(mem::size_of::<u64>()).zip(lanes) {
+        chunk.copy_from_slice(&lane.to_le_bytes());
+    }
+}
+
 #[inline(always)]
 // Generics in rust is great
 fn rotate_left<T, const N: usize>(x: Simd<T, N>, k: T) -> Simd<T, N>
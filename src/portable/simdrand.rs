@@ -1,37 +1,263 @@
-use std::simd::{f64x4, f64x8, u64x4, u64x8};
+use std::simd::num::SimdUint;
+use std::simd::{f64x4, f64x8, u64x4, u64x8, LaneCount, Simd, SimdElement, SupportedLaneCount};
 
-pub trait SimdRandX4 {
-    fn next_u64x4(&mut self) -> u64x4;
+/// Vectorized generator producing `N` lanes of 64bit values at a time.
+/// Use this to write code generic over the lane count, the [`SimdRandX4`] and [`SimdRandX8`]
+/// traits are conveniences implemented for every `SimdRand<4>` and `SimdRand<8>`.
+pub trait SimdRand<const N: usize>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    fn next_u64s(&mut self) -> Simd<u64, N>;
 
+    /// Uniformly distributed values in `[0, 1)`, using the upper 53 bits of [`SimdRand::next_u64s`].
     #[inline(always)]
-    fn next_f64x4(&mut self) -> f64x4 {
-        let v = self.next_u64x4();
+    fn next_f64s(&mut self) -> Simd<f64, N> {
+        let v = self.next_u64s();
+
+        (v >> Simd::splat(11)).cast::<f64>() * Simd::splat(1.0 / (1u64 << 53) as f64)
+    }
+
+    /// Fills `dest` with the values of consecutive [`SimdRand::next_u64s`] vectors in lane order,
+    /// regardless of the alignment of `dest`.
+    /// The unused lanes of the last vector are discarded, so the next call starts with a new vector.
+    #[inline(always)]
+    fn fill_u64(&mut self, dest: &mut [u64]) {
+        fill(dest, || [self.next_u64s()]);
+    }
+
+    /// Fills `dest` with values of [`SimdRand::next_f64s`], see [`SimdRand::fill_u64`].
+    #[inline(always)]
+    fn fill_f64(&mut self, dest: &mut [f64]) {
+        fill(dest, || [self.next_f64s()]);
+    }
+
+    /// Fills `dest` with the halves of [`SimdRand::next_u64s`] values, lower half first, see [`SimdRand::fill_u64`].
+    #[inline(always)]
+    fn fill_u32(&mut self, dest: &mut [u32]) {
+        fill(dest, || split_u64s(self.next_u64s()));
+    }
+
+    /// Fills `dest` with uniformly distributed values in `[0, 1)`,
+    /// using the upper 24 bits of the values [`SimdRand::fill_u32`] would write.
+    #[inline(always)]
+    fn fill_f32(&mut self, dest: &mut [f32]) {
+        fill(dest, || {
+            split_u64s(self.next_u64s())
+                .map(|v| (v >> Simd::splat(8)).cast::<f32>() * Simd::splat(1.0 / (1u32 << 24) as f32))
+        });
+    }
+}
+
+/// Splits `N` 64bit values into `2 * N` 32bit values, in the order of the little endian representation.
+#[inline(always)]
+fn split_u64s<const N: usize>(v: Simd<u64, N>) -> [Simd<u32, N>; 2]
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let (lo, hi) = v.cast::<u32>().interleave((v >> Simd::splat(32)).cast::<u32>());
+    [lo, hi]
+}
+
+/// Fills `dest` with the lanes of the vectors returned by `next`, in order.
+/// The elements up to the first vector boundary of `dest` are written one at a time,
+/// the body with aligned vector stores and the tail one at a time again.
+/// When the head doesn't take a whole vector, every vector of the body is
+/// the remaining lanes of the previous vector followed by the first lanes of the next one,
+/// so the output doesn't depend on the alignment of `dest`.
+#[inline(always)]
+fn fill<T, const N: usize, const K: usize>(dest: &mut [T], mut next: impl FnMut() -> [Simd<T, N>; K])
+where
+    T: SimdElement + Default,
+    LaneCount<N>: SupportedLaneCount,
+{
+    // The vectors of the last call to `next` and the number of them handed out so far
+    let mut vectors = [Simd::default(); K];
+    let mut drawn = K;
+    let mut next_vector = || {
+        if drawn == K {
+            vectors = next();
+            drawn = 0;
+        }
+        drawn += 1;
+        vectors[drawn - 1]
+    };
+
+    // SAFETY: the elements are plain numbers, so any bits are valid for both `T` and the vectors of `T`
+    let (head, body, tail) = unsafe { dest.align_to_mut::<Simd<T, N>>() };
+
+    // The last vector drawn and the number of its lanes written so far
+    let mut carry = Simd::default();
+    let mut used = N;
+
+    fill_lanes(head, &mut carry, &mut used, &mut next_vector);
+    if used == N {
+        for vector in body {
+            *vector = next_vector();
+        }
+    } else {
+        for vector in body {
+            let v = next_vector();
+            let lanes = [carry.to_array(), v.to_array()];
+            *vector = Simd::from_slice(&lanes.as_flattened()[used..used + N]);
+            carry = v;
+        }
+    }
+    fill_lanes(tail, &mut carry, &mut used, &mut next_vector);
+}
+
+/// Writes `dest` one lane at a time, continuing with the unused lanes of `carry`
+/// and drawing a new vector from `next` whenever they run out.
+#[inline(always)]
+fn fill_lanes<T: SimdElement, const N: usize>(
+    dest: &mut [T],
+    carry: &mut Simd<T, N>,
+    used: &mut usize,
+    next: &mut impl FnMut() -> Simd<T, N>,
+) where
+    LaneCount<N>: SupportedLaneCount,
+{
+    for value in dest {
+        if *used == N {
+            *carry = next();
+            *used = 0;
+        }
+        *value = carry[*used];
+        *used += 1;
+    }
+}
+
+pub trait SimdRandX4: SimdRand<4> {
+    #[inline(always)]
+    fn next_u64x4(&mut self) -> u64x4 {
+        self.next_u64s()
+    }
 
-        f64x4::from_array([
-            (v[0] >> 11) as f64 * (1.0 / (1u64 << 53) as f64),
-            (v[1] >> 11) as f64 * (1.0 / (1u64 << 53) as f64),
-            (v[2] >> 11) as f64 * (1.0 / (1u64 << 53) as f64),
-            (v[3] >> 11) as f64 * (1.0 / (1u64 << 53) as f64),
-        ])
+    #[inline(always)]
+    fn next_f64x4(&mut self) -> f64x4 {
+        self.next_f64s()
     }
 }
 
-pub trait SimdRandX8 {
-    fn next_u64x8(&mut self) -> u64x8;
+impl<R: SimdRand<4> + ?Sized> SimdRandX4 for R {}
+
+pub trait SimdRandX8: SimdRand<8> {
+    #[inline(always)]
+    fn next_u64x8(&mut self) -> u64x8 {
+        self.next_u64s()
+    }
 
     #[inline(always)]
     fn next_f64x8(&mut self) -> f64x8 {
-        let v = self.next_u64x8();
-
-        f64x8::from_array([
-            (v[0] >> 11) as f64 * (1.0 / (1u64 << 53) as f64),
-            (v[1] >> 11) as f64 * (1.0 / (1u64 << 53) as f64),
-            (v[2] >> 11) as f64 * (1.0 / (1u64 << 53) as f64),
-            (v[3] >> 11) as f64 * (1.0 / (1u64 << 53) as f64),
-            (v[4] >> 11) as f64 * (1.0 / (1u64 << 53) as f64),
-            (v[5] >> 11) as f64 * (1.0 / (1u64 << 53) as f64),
-            (v[6] >> 11) as f64 * (1.0 / (1u64 << 53) as f64),
-            (v[7] >> 11) as f64 * (1.0 / (1u64 << 53) as f64),
-        ])
+        self.next_f64s()
+    }
+}
+
+impl<R: SimdRand<8> + ?Sized> SimdRandX8 for R {}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::SeedableRng;
+    use serial_test::parallel;
+
+    use crate::portable::{Xoshiro256PlusPlusX, Xoshiro256PlusPlusX4, Xoshiro256PlusPlusX8};
+    use crate::testutil::vector_stream;
+
+    use super::*;
+
+    fn assert_same_as_scalar_conversion<const N: usize, R: SimdRand<N> + SeedableRng>()
+    where
+        LaneCount<N>: SupportedLaneCount,
+    {
+        let mut rng = R::seed_from_u64(0);
+        let mut expected = R::seed_from_u64(0);
+        for _ in 0..16 {
+            let values = rng.next_f64s();
+            let expected = expected.next_u64s();
+            for i in 0..N {
+                assert_eq!(values[i], (expected[i] >> 11) as f64 * (1.0 / (1u64 << 53) as f64));
+            }
+        }
+    }
+
+    #[test]
+    #[parallel]
+    fn generic_over_lanes() {
+        assert_same_as_scalar_conversion::<4, Xoshiro256PlusPlusX4>();
+        assert_same_as_scalar_conversion::<8, Xoshiro256PlusPlusX8>();
+
+        let mut x4 = Xoshiro256PlusPlusX4::seed_from_u64(0);
+        let mut expected = Xoshiro256PlusPlusX4::seed_from_u64(0);
+        assert_eq!(x4.next_u64x4(), expected.next_u64s());
+        assert_eq!(x4.next_f64x4(), expected.next_f64s());
+
+        let mut x8 = Xoshiro256PlusPlusX8::seed_from_u64(0);
+        let mut expected = Xoshiro256PlusPlusX8::seed_from_u64(0);
+        assert_eq!(x8.next_u64x8(), expected.next_u64s());
+        assert_eq!(x8.next_f64x8(), expected.next_f64s());
+    }
+
+    fn to_unit_f32(v: u32) -> f32 {
+        (v >> 8) as f32 * (1.0 / (1u32 << 24) as f32)
+    }
+
+    fn assert_fills<const N: usize>()
+    where
+        LaneCount<N>: SupportedLaneCount,
+    {
+        type Rng<const N: usize> = Xoshiro256PlusPlusX<N>;
+
+        let u32s = |rng: &mut Rng<N>| -> Vec<u32> {
+            let values = rng.next_u64s().to_array();
+            values.iter().flat_map(|v| [*v as u32, (v >> 32) as u32]).collect()
+        };
+
+        for offset in 0..2 * N {
+            for len in [0, 1, N - 1, N, 3 * N + 1, 100] {
+                let mut rng = Rng::<N>::seed_from_u64(0);
+                let mut values = vec![0u64; offset + len + 1];
+                rng.fill_u64(&mut values[offset..offset + len]);
+                // Consecutive fills continue with the next vector
+                rng.fill_u64(&mut values[offset + len..]);
+                let mut expected = Rng::<N>::seed_from_u64(0);
+                let mut stream = vector_stream(len, || expected.next_u64s().to_array().to_vec());
+                stream.push(expected.next_u64s()[0]);
+                assert_eq!(&values[offset..], &stream[..]);
+
+                let mut rng = Rng::<N>::seed_from_u64(0);
+                let mut values = vec![0f64; offset + len];
+                rng.fill_f64(&mut values[offset..]);
+                let mut expected = Rng::<N>::seed_from_u64(0);
+                let stream = vector_stream(len, || expected.next_f64s().to_array().to_vec());
+                assert_eq!(&values[offset..], &stream[..]);
+
+                let mut rng = Rng::<N>::seed_from_u64(0);
+                let mut values = vec![0u32; offset + len];
+                rng.fill_u32(&mut values[offset..]);
+                let mut expected = Rng::<N>::seed_from_u64(0);
+                let stream = vector_stream(len, || u32s(&mut expected));
+                assert_eq!(&values[offset..], &stream[..]);
+
+                let mut rng = Rng::<N>::seed_from_u64(0);
+                let mut values = vec![0f32; offset + len];
+                rng.fill_f32(&mut values[offset..]);
+                let stream: Vec<_> = stream.into_iter().map(to_unit_f32).collect();
+                assert_eq!(&values[offset..], &stream[..]);
+            }
+        }
+    }
+
+    #[test]
+    #[parallel]
+    fn fills() {
+        assert_fills::<2>();
+        assert_fills::<4>();
+        assert_fills::<8>();
+        assert_fills::<16>();
+
+        let mut rng = Xoshiro256PlusPlusX8::seed_from_u64(0);
+        let mut values = vec![0.0; 1001];
+        rng.fill_f64(&mut values);
+        assert!(values.iter().all(|v| (0.0..1.0).contains(v)));
     }
 }
@@ -0,0 +1,330 @@
+use std::{
+    ops::{Deref, DerefMut},
+    simd::{LaneCount, Simd, SupportedLaneCount},
+};
+
+use crate::xoshiro::{self, LaneBuffer};
+
+use super::{read_u64_into_vec, rotate_left, write_u64_lanes_into_seed, SimdRand};
+
+pub type Xoshiro256PlusX4 = Xoshiro256PlusX<4>;
+pub type Xoshiro256PlusX4Seed = Xoshiro256PlusXSeed<4>;
+pub type Xoshiro256PlusX8 = Xoshiro256PlusX<8>;
+pub type Xoshiro256PlusX8Seed = Xoshiro256PlusXSeed<8>;
+
+/// Seed of [`Xoshiro256PlusX`], `32 * N` bytes holding the 4 state vectors one after another.
+pub struct Xoshiro256PlusXSeed<const N: usize>([[u8; 32]; N]);
+
+impl<const N: usize> Xoshiro256PlusXSeed<N> {
+    /// `M` must be `32 * N`, checked at compile time.
+    pub fn new<const M: usize>(seed: [u8; M]) -> Self {
+        const { assert!(M == 32 * N) };
+        let mut result = Self::default();
+        result.copy_from_slice(&seed);
+        result
+    }
+
+    /// The seed as a `[u8; 32 * N]`, `M` must be `32 * N`, checked at compile time.
+    pub fn as_array<const M: usize>(&self) -> &[u8; M] {
+        const { assert!(M == 32 * N) };
+        self.0.as_flattened().try_into().unwrap()
+    }
+
+    /// See [`Self::as_array`].
+    pub fn as_array_mut<const M: usize>(&mut self) -> &mut [u8; M] {
+        const { assert!(M == 32 * N) };
+        self.0.as_flattened_mut().try_into().unwrap()
+    }
+}
+
+impl<const N: usize, const M: usize> Into<Xoshiro256PlusXSeed<N>> for [u8; M] {
+    fn into(self) -> Xoshiro256PlusXSeed<N> {
+        Xoshiro256PlusXSeed::new(self)
+    }
+}
+
+impl<const N: usize> Into<Xoshiro256PlusXSeed<N>> for Vec<u8> {
+    fn into(self) -> Xoshiro256PlusXSeed<N> {
+        assert!(self.len() == 32 * N);
+        let mut seed = Xoshiro256PlusXSeed::default();
+        seed.copy_from_slice(&self);
+        seed
+    }
+}
+
+impl<const N: usize> Deref for Xoshiro256PlusXSeed<N> {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_flattened()
+    }
+}
+impl<const N: usize> DerefMut for Xoshiro256PlusXSeed<N> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.0.as_flattened_mut()
+    }
+}
+
+impl<const N: usize> Default for Xoshiro256PlusXSeed<N> {
+    fn default() -> Xoshiro256PlusXSeed<N> {
+        Xoshiro256PlusXSeed([[0; 32]; N])
+    }
+}
+
+/// `M` must be `32 * N`, see [`Xoshiro256PlusXSeed::as_array`].
+impl<const N: usize, const M: usize> AsRef<[u8; M]> for Xoshiro256PlusXSeed<N> {
+    fn as_ref(&self) -> &[u8; M] {
+        self.as_array()
+    }
+}
+
+impl<const N: usize> AsMut<[u8]> for Xoshiro256PlusXSeed<N> {
+    fn as_mut(&mut self) -> &mut [u8] {
+        self
+    }
+}
+
+/// Xoshiro256+ running `N` independent lanes in `Simd<u64, N>` vectors.
+/// Any supported lane count works, e.g. 2 lanes for SSE-class hardware or 16 lanes to unroll on AVX512,
+/// with [`Xoshiro256PlusX4`] and [`Xoshiro256PlusX8`] as the common widths.
+/// Lane `i` is fully determined by its scalar state, so the same lanes produce the same streams for every `N`.
+pub struct Xoshiro256PlusX<const N: usize>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    s0: Simd<u64, N>,
+    s1: Simd<u64, N>,
+    s2: Simd<u64, N>,
+    s3: Simd<u64, N>,
+    // Number of splits leading to this generator, see `SplittableRng`
+    depth: u8,
+    // Lanes not yet returned through `RngCore`
+    buffer: LaneBuffer<N>,
+}
+
+xoshiro::vector_xoshiro! {
+    impl<const N: usize> Xoshiro256PlusX<N> {
+        seed: Xoshiro256PlusXSeed<N>,
+        lanes: N,
+        scalar: Xoshiro256Plus,
+        read_seed_lanes: |src| read_u64_into_vec(src).to_array(),
+        write_seed_lanes: write_u64_lanes_into_seed,
+    }
+    where
+        LaneCount<N>: SupportedLaneCount,
+}
+
+impl<const N: usize> Xoshiro256PlusX<N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    /// Vector states, `states[k][i]` being word `k` of the state of lane `i`.
+    fn from_states(states: [[u64; N]; 4]) -> Self {
+        let [s0, s1, s2, s3] = states.map(Simd::from_array);
+
+        Self {
+            s0,
+            s1,
+            s2,
+            s3,
+            depth: 0,
+            buffer: LaneBuffer::new(),
+        }
+    }
+
+    fn states(&self) -> [[u64; N]; 4] {
+        [self.s0, self.s1, self.s2, self.s3].map(|s| s.to_array())
+    }
+
+    #[inline(always)]
+    fn next_lanes(&mut self) -> [u64; N] {
+        self.next_u64s().to_array()
+    }
+}
+
+impl<const N: usize> SimdRand<N> for Xoshiro256PlusX<N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    fn next_u64s(&mut self) -> Simd<u64, N> {
+        let result = self.s0 + self.s3;
+
+        let t = self.s1 << Simd::splat(17);
+
+        self.s2 ^= self.s0;
+        self.s3 ^= self.s1;
+        self.s1 ^= self.s2;
+        self.s0 ^= self.s3;
+
+        self.s2 ^= t;
+
+        self.s3 = rotate_left(self.s3, 45);
+
+        return result;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::mem;
+
+    use serial_test::parallel;
+
+    use crate::testutil::{REF_SEED_256, REF_SEED_512};
+
+    use super::*;
+
+    /// Seed where word `k` of every lane is `k + 1`, for any lane count.
+    fn ref_seed<const N: usize>() -> Xoshiro256PlusXSeed<N> {
+        let mut seed = Xoshiro256PlusXSeed::default();
+        for (k, chunk) in seed.chunks_exact_mut(N * mem::size_of::<u64>()).enumerate() {
+            write_u64_lanes_into_seed([k as u64 + 1; N], chunk);
+        }
+        seed
+    }
+
+    #[test]
+    #[parallel]
+    fn ref_seeds() {
+        assert_eq!(*ref_seed::<4>(), REF_SEED_256);
+        assert_eq!(*ref_seed::<8>(), REF_SEED_512);
+        assert_eq!(*Xoshiro256PlusX4Seed::new(REF_SEED_256), REF_SEED_256);
+
+        let mut seed = ref_seed::<8>();
+        assert_eq!(seed.as_array(), &REF_SEED_512);
+        let bytes: &[u8; 256] = seed.as_ref();
+        assert_eq!(bytes, &REF_SEED_512);
+        seed.as_array_mut::<256>()[0] = 0;
+        assert_eq!(seed[0], 0);
+    }
+
+    // Runs the same tests for a number of lane counts
+    macro_rules! lane_tests {
+        ($name:ident, $lanes:literal) => {
+            mod $name {
+                use itertools::Itertools;
+                use rand_core::{RngCore, SeedableRng};
+                use serial_test::parallel;
+                use std::simd::*;
+
+                use crate::testutil::{test_uniform_distribution, DOUBLE_RANGE};
+
+                use super::super::*;
+                use super::ref_seed;
+
+                const N: usize = $lanes;
+                type RngSeed = Xoshiro256PlusXSeed<N>;
+                type RngImpl = Xoshiro256PlusX<N>;
+
+                #[test]
+                #[parallel]
+                fn reference() {
+                    let seed: RngSeed = ref_seed();
+                    let mut rng = RngImpl::from_seed(seed);
+                    // These values were produced with the reference implementation:
+                    // http://xoshiro.di.unimi.it/xoshiro256plus.c
+                    #[rustfmt::skip]
+                    let expected = [
+                        5,
+                        211106232532999,
+                        211106635186183,
+                        9223759065350669058,
+                        9250833439874351877,
+                        13862484359527728515,
+                        2346507365006083650,
+                        1168864526675804870,
+                        34095955243042024,
+                        3466914240207415127,
+                    ];
+                    for e in expected {
+                        let mem = rng.next_u64s();
+                        for &v in mem.as_array().into_iter() {
+                            assert_eq!(v, e);
+                        }
+                    }
+                }
+
+                crate::testutil::xoshiro_tests!(
+                    Xoshiro256PlusX<N>,
+                    seed: Xoshiro256PlusXSeed<N>,
+                    scalar: rand_xoshiro::Xoshiro256Plus,
+                    lanes: $lanes,
+                    portable: Xoshiro256PlusX<16>,
+                );
+
+                #[test]
+                #[parallel]
+                fn sample_u64s() {
+                    let mut seed: RngSeed = Default::default();
+                    rand::thread_rng().fill_bytes(&mut *seed);
+                    let mut rng = RngImpl::from_seed(seed);
+
+                    let values = *rng.next_u64s().as_array();
+
+                    assert!(values.iter().all(|&v| v != 0));
+                    assert!(values.iter().unique().count() == values.len());
+                    println!("{values:?}");
+
+                    let values = *rng.next_u64s().as_array();
+
+                    assert!(values.iter().all(|&v| v != 0));
+                    assert!(values.iter().unique().count() == values.len());
+                    println!("{values:?}");
+                }
+
+                #[test]
+                #[parallel]
+                fn sample_f64s() {
+                    let mut seed: RngSeed = Default::default();
+                    rand::thread_rng().fill_bytes(&mut *seed);
+                    let mut rng = RngImpl::from_seed(seed);
+
+                    let values = *rng.next_f64s().as_array();
+
+                    assert!(values.iter().all(|&v| v != 0.0));
+                    println!("{values:?}");
+
+                    let values = *rng.next_f64s().as_array();
+
+                    assert!(values.iter().all(|&v| v != 0.0));
+                    println!("{values:?}");
+                }
+
+                #[test]
+                #[parallel]
+                fn sample_f64s_distribution() {
+                    let mut seed: RngSeed = Default::default();
+                    rand::thread_rng().fill_bytes(&mut *seed);
+                    let mut rng = RngImpl::from_seed(seed);
+
+                    let mut current: Option<Simd<f64, N>> = None;
+                    let mut current_index: usize = 0;
+
+                    test_uniform_distribution::<10_000_000, f64>(
+                        || match &current {
+                            Some(vector) if current_index < N => {
+                                let result = vector[current_index];
+                                current_index += 1;
+                                return result;
+                            }
+                            _ => {
+                                current_index = 0;
+                                let vector = rng.next_f64s();
+                                let result = vector[current_index];
+                                current = Some(vector);
+                                current_index += 1;
+                                return result;
+                            }
+                        },
+                        DOUBLE_RANGE,
+                    );
+                }
+            }
+        };
+    }
+
+    lane_tests!(x2, 2);
+    lane_tests!(x4, 4);
+    lane_tests!(x8, 8);
+    lane_tests!(x16, 16);
+}
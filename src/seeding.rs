@@ -0,0 +1,422 @@
+//! Seed derivation shared by all generators.
+//!
+//! # Keys
+//!
+//! [`SeedFromKey`] derives seeds from keys of any length, e.g. names of experiments such as `"run-2026-10/replicate-17"`.
+//! The expansion is part of the stable API, the same key will give the same seeds on every version of this crate:
+//!
+//! 1. The key is hashed into a 256bit digest `d[0..4]` of 64bit words.
+//!    Every word starts at `d[j] = mix64(len + (j + 1) * GAMMA)` where `len` is the length of the key in bytes,
+//!    then for every 8 byte chunk `c` of the key, read as little-endian and with the last chunk zero-padded,
+//!    every word is updated to `d[j] = mix64(d[j] ^ c)`.
+//! 2. The digest is expanded into blocks of four 64bit words, block `b` being `mix64(d[j] + (b + 1) * GAMMA)` for `j` in `0..4`.
+//!
+//! `GAMMA` is `0x9e3779b97f4a7c15` and `mix64` is the SplitMix64 output function, all arithmetic is wrapping.
+//!
+//! Every 256bit state is one block: lane `i` of the xoshiro generators gets block `i` as its state,
+//! and 256bit seeds like the `[u8; 32]` of Shishua or `from_scalar_seed` get block 0 in little-endian.
+//! So lane `i` produces the same stream for a key regardless of the lane width or implementation of the generator.
+//!
+//! # Entropy
+//!
+//! [`FromOsEntropy`] seeds any generator from the OS entropy source through `getrandom`,
+//! without depending on the `rand` crate. On x86_64, `specific::rdrand` can seed from the CPU instead.
+//!
+//! # Splitting
+//!
+//! [`SplittableRng`] splits generators in two for fork-join parallelism, e.g. with `rayon::join`.
+//! The xoshiro generators split by jumping ahead, the Shishua generators by key derivation, see the implementations.
+//!
+//! # Seed sequences
+//!
+//! [`SeedSequence`] is a port of NumPy's `numpy.random.SeedSequence`, producing the same words for the same entropy.
+//! It can spawn any number of independent children, e.g. one per worker or replicate, which seed generators directly:
+//!
+//! ```
+//! use rand_core::SeedableRng;
+//! use simd_rand::portable::*;
+//! use simd_rand::seeding::SeedSequence;
+//!
+//! let mut root = SeedSequence::new(0x8c3c010cb4754c905776bdac5ee7501);
+//! let rngs: Vec<_> = root
+//!     .spawn(4)
+//!     .iter()
+//!     .map(|child| Xoshiro256PlusPlusX8::from_seed(child.generate_seed()))
+//!     .collect();
+//! ```
+
+use rand_core::{RngCore, SeedableRng};
+
+/// The golden ratio increment of SplitMix64.
+pub(crate) const GAMMA: u64 = 0x9e3779b97f4a7c15;
+
+/// The output function of SplitMix64.
+#[inline(always)]
+pub(crate) fn mix64(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+/// Derives seeds from keys of any length, see the [module documentation](self) for the expansion.
+pub trait SeedFromKey: Sized {
+    fn from_key(key: &[u8]) -> Self;
+
+    fn from_str_key(key: &str) -> Self {
+        Self::from_key(key.as_bytes())
+    }
+}
+
+impl SeedFromKey for [u8; 32] {
+    fn from_key(key: &[u8]) -> Self {
+        let mut seed = [0u8; 32];
+        for (chunk, word) in seed.chunks_exact_mut(8).zip(KeyDigest::new(key).block(0)) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        seed
+    }
+}
+
+const POOL_SIZE: usize = 4;
+const INIT_A: u32 = 0x43b0d7e5;
+const MULT_A: u32 = 0x931e8875;
+const INIT_B: u32 = 0x8b51f9dd;
+const MULT_B: u32 = 0x58f38ded;
+const MIX_MULT_L: u32 = 0xca01f9dd;
+const MIX_MULT_R: u32 = 0x4973f715;
+const XSHIFT: u32 = u32::BITS / 2;
+
+/// Port of NumPy's `SeedSequence` with the default pool size, see the [module documentation](self).
+///
+/// Entropy is given as 32bit words, which is how NumPy coerces its inputs:
+/// an integer is split into words least significant first,
+/// and a sequence of integers below 2^32 gives one word per integer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SeedSequence {
+    entropy: Vec<u32>,
+    spawn_key: Vec<u32>,
+    pool: [u32; POOL_SIZE],
+    n_children_spawned: u32,
+}
+
+impl SeedSequence {
+    /// Same as `SeedSequence(entropy)` in NumPy.
+    pub fn new(entropy: u128) -> Self {
+        let mut words = vec![entropy as u32];
+        let mut rest = entropy >> u32::BITS;
+        while rest != 0 {
+            words.push(rest as u32);
+            rest >>= u32::BITS;
+        }
+        Self::from_words(&words, &[])
+    }
+
+    /// Same as `SeedSequence(entropy, spawn_key=spawn_key)` in NumPy.
+    pub fn from_words(entropy: &[u32], spawn_key: &[u32]) -> Self {
+        let mut assembled = entropy.to_vec();
+        if !spawn_key.is_empty() && assembled.len() < POOL_SIZE {
+            // Same as NumPy (since 1.19), padding only when spawned keeps small unspawned entropy compatible
+            assembled.resize(POOL_SIZE, 0);
+        }
+        assembled.extend_from_slice(spawn_key);
+
+        Self {
+            entropy: entropy.to_vec(),
+            spawn_key: spawn_key.to_vec(),
+            pool: mix_entropy(&assembled),
+            n_children_spawned: 0,
+        }
+    }
+
+    pub fn entropy(&self) -> &[u32] {
+        &self.entropy
+    }
+
+    pub fn spawn_key(&self) -> &[u32] {
+        &self.spawn_key
+    }
+
+    pub fn n_children_spawned(&self) -> u32 {
+        self.n_children_spawned
+    }
+
+    /// Spawns `n` children, same as `spawn(n)` in NumPy.
+    /// Child `i` gets the spawn key of its parent extended with `i`, counting every child spawned so far,
+    /// so a child only depends on its position in the tree and not on how many children are spawned at a time.
+    pub fn spawn(&mut self, n: u32) -> Vec<SeedSequence> {
+        let start = self.n_children_spawned;
+        self.n_children_spawned += n;
+
+        (start..start + n)
+            .map(|i| {
+                let mut spawn_key = self.spawn_key.clone();
+                spawn_key.push(i);
+                Self::from_words(&self.entropy, &spawn_key)
+            })
+            .collect()
+    }
+
+    /// Fills `dst` with state words, same as `generate_state(dst.len())` in NumPy.
+    pub fn generate_state(&self, dst: &mut [u32]) {
+        let mut hash_const = INIT_B;
+        for (word, &pool) in dst.iter_mut().zip(self.pool.iter().cycle()) {
+            let mut value = pool ^ hash_const;
+            hash_const = hash_const.wrapping_mul(MULT_B);
+            value = value.wrapping_mul(hash_const);
+            *word = value ^ (value >> XSHIFT);
+        }
+    }
+
+    /// Fills `dst` with state words, same as `generate_state(dst.len(), np.uint64)` in NumPy.
+    pub fn generate_state_u64(&self, dst: &mut [u64]) {
+        let mut words = vec![0u32; dst.len() * 2];
+        self.generate_state(&mut words);
+        for (word, pair) in dst.iter_mut().zip(words.chunks_exact(2)) {
+            *word = pair[0] as u64 | (pair[1] as u64) << u32::BITS;
+        }
+    }
+
+    /// Generates a seed for any generator, such as `Xoshiro256PlusPlusX8Seed` or the `[u8; 32]` of Shishua.
+    /// The bytes of the seed are the state words in little-endian.
+    pub fn generate_seed<S: Default + AsMut<[u8]>>(&self) -> S {
+        let mut seed = S::default();
+        let bytes = seed.as_mut();
+        let mut words = vec![0u32; bytes.len().div_ceil(4)];
+        self.generate_state(&mut words);
+        for (chunk, word) in bytes.chunks_mut(4).zip(words) {
+            chunk.copy_from_slice(&word.to_le_bytes()[..chunk.len()]);
+        }
+        seed
+    }
+}
+
+fn hashmix(value: u32, hash_const: &mut u32) -> u32 {
+    let mut value = value ^ *hash_const;
+    *hash_const = hash_const.wrapping_mul(MULT_A);
+    value = value.wrapping_mul(*hash_const);
+    value ^ (value >> XSHIFT)
+}
+
+fn mix(x: u32, y: u32) -> u32 {
+    let result = MIX_MULT_L.wrapping_mul(x).wrapping_sub(MIX_MULT_R.wrapping_mul(y));
+    result ^ (result >> XSHIFT)
+}
+
+fn mix_entropy(entropy: &[u32]) -> [u32; POOL_SIZE] {
+    let mut hash_const = INIT_A;
+    let mut pool = [0; POOL_SIZE];
+
+    // Entropy up to the pool size, running the hash out if there is less entropy
+    for (i, word) in pool.iter_mut().enumerate() {
+        *word = hashmix(entropy.get(i).copied().unwrap_or(0), &mut hash_const);
+    }
+
+    // Mix all words together, so that later words affect earlier ones
+    for i_src in 0..POOL_SIZE {
+        for i_dst in 0..POOL_SIZE {
+            if i_src != i_dst {
+                pool[i_dst] = mix(pool[i_dst], hashmix(pool[i_src], &mut hash_const));
+            }
+        }
+    }
+
+    // Remaining entropy is mixed into every word of the pool
+    for &word in entropy.iter().skip(POOL_SIZE) {
+        for dst in pool.iter_mut() {
+            *dst = mix(*dst, hashmix(word, &mut hash_const));
+        }
+    }
+
+    pool
+}
+
+/// Fills `dest` from the OS entropy source, e.g. the `getrandom` syscall or `/dev/urandom` on Linux.
+pub fn fill_from_os_entropy(dest: &mut [u8]) -> Result<(), rand_core::Error> {
+    getrandom::getrandom(dest).map_err(|e| e.code().into())
+}
+
+/// Seeds generators from the OS entropy source, implemented for every [`SeedableRng`].
+pub trait FromOsEntropy: SeedableRng {
+    /// # Panics
+    ///
+    /// If the OS entropy source fails, see [`FromOsEntropy::try_from_os_entropy`].
+    fn from_os_entropy() -> Self {
+        match Self::try_from_os_entropy() {
+            Ok(rng) => rng,
+            Err(e) => panic!("Failed to read OS entropy: {e}"),
+        }
+    }
+
+    fn try_from_os_entropy() -> Result<Self, rand_core::Error> {
+        let mut seed = Self::Seed::default();
+        fill_from_os_entropy(seed.as_mut())?;
+        Ok(Self::from_seed(seed))
+    }
+}
+
+impl<R: SeedableRng> FromOsEntropy for R {}
+
+/// Generators which can be split into two statistically independent generators, for fork-join parallelism.
+///
+/// Splitting is deterministic: the generator at any position of a split tree only depends on the root generator
+/// and the path from the root, not on the order the splits happen in or which threads they happen on.
+/// Values drawn from a generator before splitting it are part of that path.
+pub trait SplittableRng: Sized {
+    /// Splits the generator into two halves, see the implementations for how far apart they are.
+    /// The xoshiro generators jump ahead, siblings at depth `d` being `2^(127 - d)` steps apart,
+    /// and rekey after 128 levels, so split trees can be of any depth.
+    fn split(self) -> (Self, Self);
+}
+
+/// Seeds for the halves of a split through key derivation.
+/// Draws a 32 byte key from `rng`, the halves are seeded by [`SeedFromKey`] with the key followed by a `0` or `1` byte.
+pub(crate) fn split_seeds(rng: &mut impl RngCore) -> ([u8; 32], [u8; 32]) {
+    let mut key = [0u8; 33];
+    rng.fill_bytes(&mut key[..32]);
+    let left = <[u8; 32]>::from_key(&key);
+    key[32] = 1;
+    (left, <[u8; 32]>::from_key(&key))
+}
+
+/// The digest of step 1 of the key expansion.
+pub(crate) struct KeyDigest([u64; 4]);
+
+impl KeyDigest {
+    pub(crate) fn new(key: &[u8]) -> Self {
+        let len = key.len() as u64;
+        let mut digest = [1, 2, 3, 4].map(|j: u64| mix64(len.wrapping_add(j.wrapping_mul(GAMMA))));
+
+        for chunk in key.chunks(8) {
+            let mut bytes = [0u8; 8];
+            bytes[..chunk.len()].copy_from_slice(chunk);
+            let c = u64::from_le_bytes(bytes);
+            for d in &mut digest {
+                *d = mix64(*d ^ c);
+            }
+        }
+
+        Self(digest)
+    }
+
+    /// Block `b` of step 2 of the key expansion.
+    pub(crate) fn block(&self, b: u64) -> [u64; 4] {
+        let offset = b.wrapping_add(1).wrapping_mul(GAMMA);
+        self.0.map(|d| mix64(d.wrapping_add(offset)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serial_test::parallel;
+
+    use crate::portable::{SimdRandX8, Xoshiro256PlusPlusX8, Xoshiro256PlusPlusX8Seed};
+
+    use super::*;
+
+    #[test]
+    #[parallel]
+    fn stable_expansion() {
+        // Changing any of these values breaks the stability guarantee of the key expansion
+        #[rustfmt::skip]
+        let expected: [u8; 32] = [
+            254, 76, 110, 38, 200, 149, 253, 33, 121, 13, 228, 118, 189, 213, 49, 25,
+            117, 59, 204, 135, 248, 222, 252, 19, 6, 108, 39, 125, 58, 45, 95, 96,
+        ];
+        assert_eq!(<[u8; 32]>::from_str_key("run-2026-10/replicate-17"), expected);
+        assert_eq!(
+            KeyDigest::new(b"run-2026-10/replicate-17").block(1),
+            [
+                0x45fae02da734faa5,
+                0x654e6a442fd7a982,
+                0x129cb21a260e60e0,
+                0x807f4ecfd7e98388
+            ]
+        );
+
+        #[rustfmt::skip]
+        let expected: [u8; 32] = [
+            111, 126, 25, 77, 47, 221, 6, 167, 0, 124, 195, 240, 121, 62, 183, 70,
+            28, 53, 53, 33, 185, 81, 39, 238, 56, 123, 74, 50, 179, 59, 33, 78,
+        ];
+        assert_eq!(<[u8; 32]>::from_key(&[]), expected);
+    }
+
+    #[test]
+    #[parallel]
+    fn length_is_part_of_key() {
+        // The last chunk is zero-padded, so these only differ by length
+        assert_ne!(<[u8; 32]>::from_key(b"key"), <[u8; 32]>::from_key(b"key\0"));
+        assert_ne!(<[u8; 32]>::from_key(&[]), <[u8; 32]>::from_key(&[0; 8]));
+    }
+
+    #[test]
+    #[parallel]
+    fn seed_sequence_numpy_reference() {
+        // From `test_reference_data` in NumPy's test suite
+        let seq = SeedSequence::from_words(&[3735928559, 195939070, 229505742, 305419896], &[]);
+        let mut state = [0u32; 4];
+        seq.generate_state(&mut state);
+        assert_eq!(state, [3914649087, 576849849, 3593928901, 2229911004]);
+    }
+
+    #[test]
+    #[parallel]
+    fn seed_sequence_entropy_words() {
+        assert_eq!(SeedSequence::new(0).entropy(), [0]);
+        assert_eq!(SeedSequence::new(1 << 32 | 5).entropy(), [5, 1]);
+        assert_eq!(SeedSequence::new(12345), SeedSequence::from_words(&[12345], &[]));
+    }
+
+    #[test]
+    #[parallel]
+    fn seed_sequence_spawn() {
+        let mut root = SeedSequence::new(12345);
+        let mut children = root.spawn(2);
+        children.extend(root.spawn(1));
+        assert_eq!(root.n_children_spawned(), 3);
+
+        for (i, child) in children.iter_mut().enumerate() {
+            assert_eq!(child.spawn_key(), [i as u32]);
+            assert_eq!(*child, SeedSequence::from_words(&[12345], &[i as u32]));
+
+            let grandchild = child.spawn(1).remove(0);
+            assert_eq!(grandchild.spawn_key(), [i as u32, 0]);
+        }
+
+        // Spawned entropy is padded to the pool size, so the spawn key can't be mistaken for entropy
+        let unspawned = SeedSequence::from_words(&[12345, 2], &[]);
+        assert_ne!(
+            children[2].generate_seed::<[u8; 32]>(),
+            unspawned.generate_seed::<[u8; 32]>()
+        );
+    }
+
+    #[test]
+    #[parallel]
+    fn seed_sequence_seeds() {
+        let seq = SeedSequence::new(0x8c3c010cb4754c905776bdac5ee7501);
+        let mut words = [0u32; 64];
+        seq.generate_state(&mut words);
+        let bytes: Vec<u8> = words.iter().flat_map(|w| w.to_le_bytes()).collect();
+
+        assert_eq!(*seq.generate_seed::<Xoshiro256PlusPlusX8Seed>(), bytes[..]);
+        assert_eq!(seq.generate_seed::<[u8; 32]>(), bytes[..32]);
+
+        let mut words64 = [0u64; 32];
+        seq.generate_state_u64(&mut words64);
+        let bytes64: Vec<u8> = words64.iter().flat_map(|w| w.to_le_bytes()).collect();
+        assert_eq!(bytes64, bytes);
+    }
+
+    #[test]
+    #[parallel]
+    fn os_entropy() {
+        let mut a = Xoshiro256PlusPlusX8::from_os_entropy();
+        let mut b = Xoshiro256PlusPlusX8::try_from_os_entropy().unwrap();
+        assert_ne!(a.next_u64x8(), b.next_u64x8());
+
+        let mut bytes = [0u8; 64];
+        fill_from_os_entropy(&mut bytes).unwrap();
+        assert!(bytes.iter().any(|&b| b != 0));
+    }
+}
@@ -0,0 +1,440 @@
+use std::alloc::{self, Layout};
+use std::arch::x86_64::*;
+use std::cell::UnsafeCell;
+use std::mem;
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle, Thread};
+
+use rand_core::{RngCore, SeedableRng};
+
+use super::shishua::{check_buffer_size, BufferedStateBuffer, ShishuaCore, ShishuaError, DEFAULT_BUFFER_SIZE};
+use super::simdrand::*;
+
+/// Double-buffered variant of [`Shishua`](super::Shishua) which rebuffers on a background thread.
+///
+/// Owns two buffers of `BUFFER_SIZE` bytes. While one is being consumed,
+/// a worker thread regenerates the other, so that running out of buffered randomness
+/// is just a handoff to the other buffer instead of a full rebuffer on the calling thread.
+/// This removes the latency spike every `BUFFER_SIZE` bytes, at the cost of a thread per generator.
+/// The worker needs a core of its own to be of any use, otherwise it just competes with the consumer.
+/// Produces the exact same output as [`Shishua`](super::Shishua) for the same seed and `BUFFER_SIZE`.
+///
+/// # Handoff
+///
+/// Buffers are numbered in the order they are generated, buffer `n` lives in slot `n % 2`.
+/// Generating a buffer takes a claim on its number through a `claimed` counter,
+/// which gives exclusive access to the engine state and the slot until the buffer is published by bumping `filled`.
+/// The consumer hands buffers back by bumping a `consumed` counter.
+///
+/// When the consumer runs out of buffered randomness, it switches to the other buffer if it is published.
+/// If the worker hasn't started on it yet, the consumer claims it and generates it inline, same as [`Shishua`](super::Shishua) would.
+/// Only if the worker is in the middle of generating it does the consumer park until it's published,
+/// which never takes longer than generating it inline. The worker parks while both slots are full.
+///
+/// So the handoff is lock-free with an inline fallback, not wait-free: switching to a published buffer
+/// or generating an unclaimed one takes no locks, but a consumer catching the worker mid-generation blocks until it's done.
+///
+/// Dropping the generator stops and joins the worker thread.
+pub struct BackgroundShishua<const BUFFER_SIZE: usize = DEFAULT_BUFFER_SIZE> {
+    shared: Arc<Shared<BUFFER_SIZE>>,
+    worker: Option<JoinHandle<()>>,
+    // The buffer currently being consumed
+    generation: u64,
+    buffer: NonNull<u8>,
+    buffer_index: usize,
+}
+
+type Slot<const BUFFER_SIZE: usize> = Box<UnsafeCell<BufferedStateBuffer<[u8; BUFFER_SIZE]>>>;
+
+struct Shared<const BUFFER_SIZE: usize> {
+    slots: [Slot<BUFFER_SIZE>; 2],
+    // Owned by whoever holds the claim on the next buffer
+    core: UnsafeCell<ShishuaCore>,
+    // Number of buffers claimed for generation so far, `filled` or one ahead of it
+    claimed: AtomicU64,
+    // Number of buffers generated so far
+    filled: AtomicU64,
+    // Number of buffers the consumer is done with
+    consumed: AtomicU64,
+    shutdown: AtomicBool,
+    // The consumer parked until the worker publishes a buffer, if any
+    waiting: Mutex<Option<Thread>>,
+}
+
+// SAFETY: the engine state and the buffers are only ever accessed by one side at a time,
+// as coordinated through the `claimed`, `filled` and `consumed` counters (see `BackgroundShishua`)
+unsafe impl<const BUFFER_SIZE: usize> Send for Shared<BUFFER_SIZE> {}
+unsafe impl<const BUFFER_SIZE: usize> Sync for Shared<BUFFER_SIZE> {}
+
+impl<const BUFFER_SIZE: usize> Shared<BUFFER_SIZE> {
+    fn try_new(core: ShishuaCore) -> Result<Self, ShishuaError> {
+        check_buffer_size(BUFFER_SIZE)?;
+
+        // Zeroed bytes are a valid buffer, and this avoids building it on the stack first
+        let slot = || -> Result<Slot<BUFFER_SIZE>, ShishuaError> {
+            let slot = Box::try_new_zeroed().map_err(ShishuaError::Alloc)?;
+            Ok(unsafe { slot.assume_init() })
+        };
+
+        Ok(Self {
+            slots: [slot()?, slot()?],
+            core: UnsafeCell::new(core),
+            claimed: AtomicU64::new(0),
+            filled: AtomicU64::new(0),
+            consumed: AtomicU64::new(0),
+            shutdown: AtomicBool::new(false),
+            waiting: Mutex::new(None),
+        })
+    }
+
+    #[inline(always)]
+    fn slot(&self, generation: u64) -> NonNull<u8> {
+        let slot = &self.slots[(generation % 2) as usize];
+        unsafe { NonNull::new_unchecked((*slot.get()).0.as_mut_ptr()) }
+    }
+
+    /// Makes buffer `generation` available, generating it on the calling thread unless it's already claimed.
+    /// Returns `false` if the other side is still generating it.
+    /// Buffer `generation - 1` must be published and `generation - 2` consumed.
+    fn generate(&self, generation: u64) -> bool {
+        if self.filled.load(Ordering::Acquire) > generation {
+            return true;
+        }
+        let claim = self
+            .claimed
+            .compare_exchange(generation, generation + 1, Ordering::Acquire, Ordering::Relaxed);
+        if claim.is_err() {
+            return false;
+        }
+
+        // SAFETY: the claim gives exclusive access to the engine state and the slot until the buffer is published
+        unsafe {
+            let slot = &mut (*self.slots[(generation % 2) as usize].get()).0;
+            (*self.core.get()).generate_into(slot);
+        }
+        self.filled.store(generation + 1, Ordering::Release);
+        true
+    }
+
+    /// Parks the calling thread until buffer `generation` is published.
+    fn wait(&self, generation: u64) {
+        // Registered before checking `filled`, so the worker either sees us or we see the buffer
+        *self.waiting.lock().unwrap() = Some(thread::current());
+        while self.filled.load(Ordering::Acquire) <= generation {
+            thread::park();
+        }
+        self.waiting.lock().unwrap().take();
+    }
+
+    fn work(&self) {
+        loop {
+            if self.shutdown.load(Ordering::Acquire) {
+                return;
+            }
+
+            let generation = self.filled.load(Ordering::Acquire);
+            // Both slots are full until the consumer hands back the older one,
+            // and if the consumer claimed the buffer it unparks us once it's done
+            if generation >= self.consumed.load(Ordering::Acquire) + 2 || !self.generate(generation) {
+                thread::park();
+                continue;
+            }
+
+            if let Some(consumer) = self.waiting.lock().unwrap().take() {
+                consumer.unpark();
+            }
+        }
+    }
+}
+
+impl<const BUFFER_SIZE: usize> BackgroundShishua<BUFFER_SIZE> {
+    #[inline(always)]
+    pub fn buffer_index(&self) -> usize {
+        self.buffer_index
+    }
+
+    #[inline(always)]
+    pub fn next_f32(&mut self) -> f32 {
+        let v = self.next_u32();
+        (v >> 8) as f32 * (1.0f32 / (1u32 << 24) as f32)
+    }
+
+    #[inline(always)]
+    pub fn next_f64(&mut self) -> f64 {
+        let v = self.next_u64();
+        (v >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+
+    #[inline(always)]
+    fn fill_bytes_arr<const N: usize>(&mut self, dest: &mut [u8; N]) {
+        self.ensure_buffered(N);
+
+        unsafe {
+            let src = self.buffer.add(self.buffer_index).as_ptr();
+            std::ptr::copy_nonoverlapping(src, dest.as_mut_ptr(), N);
+        }
+        self.buffer_index += N;
+    }
+
+    #[inline(always)] // This should be inlined, this branch will be checked every time we sample
+    fn ensure_buffered(&mut self, size: usize) {
+        if BUFFER_SIZE - self.buffer_index < size {
+            self.next_buffer();
+        }
+    }
+
+    #[cold]
+    #[inline(never)]
+    fn next_buffer(&mut self) {
+        // Hand the current buffer back to the worker so it can be regenerated...
+        self.generation += 1;
+        self.shared.consumed.store(self.generation, Ordering::Release);
+
+        // ...and pick up the other one, generating it here if the worker hasn't started on it
+        if !self.shared.generate(self.generation) {
+            self.shared.wait(self.generation);
+        }
+        if let Some(worker) = &self.worker {
+            worker.thread().unpark();
+        }
+        self.buffer = self.shared.slot(self.generation);
+        self.buffer_index = 0;
+    }
+
+    #[cold]
+    #[inline(never)]
+    fn fill_bytes_chunked(&mut self, dest: &mut [u8]) {
+        // Can't serve more than a buffer at a time
+        for chunk in dest.chunks_mut(BUFFER_SIZE) {
+            self.fill_bytes(chunk);
+        }
+    }
+}
+
+impl<const BUFFER_SIZE: usize> BackgroundShishua<BUFFER_SIZE> {
+    /// Fallible construction of the generator.
+    /// Generates the first buffer on the calling thread and starts the worker thread.
+    ///
+    /// Returns an error instead of panicking if `BUFFER_SIZE` is invalid
+    /// (it must be a power of 2 and at least 256) or if the allocation of the buffers fails.
+    ///
+    /// # Panics
+    ///
+    /// If the worker thread can't be spawned.
+    pub fn try_from_seed(seed: [u8; 32]) -> Result<Self, ShishuaError> {
+        let shared = Arc::new(Shared::<BUFFER_SIZE>::try_new(ShishuaCore::from_seed(seed))?);
+
+        // The first buffer is generated up front, so the worker starts with the second one
+        shared.generate(0);
+
+        let worker = {
+            let shared = Arc::clone(&shared);
+            thread::Builder::new()
+                .name("shishua-rebuffer".into())
+                .spawn(move || shared.work())
+                .expect("failed to spawn Shishua rebuffer thread")
+        };
+
+        let buffer = shared.slot(0);
+        Ok(Self {
+            shared,
+            worker: Some(worker),
+            generation: 0,
+            buffer,
+            buffer_index: 0,
+        })
+    }
+}
+
+impl<const BUFFER_SIZE: usize> SeedableRng for BackgroundShishua<BUFFER_SIZE> {
+    type Seed = [u8; 32];
+
+    /// Generates the first buffer on the calling thread and starts the worker thread.
+    ///
+    /// # Panics
+    ///
+    /// If `BUFFER_SIZE` is not a power of 2 or smaller than 256, or if the worker thread can't be spawned.
+    fn from_seed(seed: Self::Seed) -> Self {
+        match Self::try_from_seed(seed) {
+            Ok(rng) => rng,
+            Err(ShishuaError::Alloc(_)) => {
+                alloc::handle_alloc_error(Layout::new::<BufferedStateBuffer<[u8; BUFFER_SIZE]>>())
+            }
+            Err(e) => panic!("{e}"),
+        }
+    }
+}
+
+impl<const BUFFER_SIZE: usize> SimdRand for BackgroundShishua<BUFFER_SIZE> {
+    #[inline(always)]
+    fn next_m256i(&mut self) -> __m256i {
+        const SIZE: usize = mem::size_of::<__m256i>();
+
+        self.ensure_buffered(SIZE);
+
+        unsafe {
+            let src = self.buffer.add(self.buffer_index).as_ptr();
+            let vector = _mm256_loadu_si256(src as *const __m256i);
+            self.buffer_index += SIZE;
+            vector
+        }
+    }
+}
+
+impl<const BUFFER_SIZE: usize> RngCore for BackgroundShishua<BUFFER_SIZE> {
+    #[inline(always)]
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0u8; 4];
+        self.fill_bytes_arr(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    #[inline(always)]
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0u8; 8];
+        self.fill_bytes_arr(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    #[inline(always)]
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        let size = dest.len();
+        if size > BUFFER_SIZE {
+            return self.fill_bytes_chunked(dest);
+        }
+
+        self.ensure_buffered(size);
+
+        unsafe {
+            let src = self.buffer.add(self.buffer_index).as_ptr();
+            std::ptr::copy_nonoverlapping(src, dest.as_mut_ptr(), size);
+        }
+        self.buffer_index += size;
+    }
+
+    #[inline(always)]
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+// SAFETY: the current buffer pointer points into the shared allocation which is kept alive by the Arc,
+// and it is only read through &mut self
+unsafe impl<const BUFFER_SIZE: usize> Send for BackgroundShishua<BUFFER_SIZE> {}
+unsafe impl<const BUFFER_SIZE: usize> Sync for BackgroundShishua<BUFFER_SIZE> {}
+
+impl<const BUFFER_SIZE: usize> Drop for BackgroundShishua<BUFFER_SIZE> {
+    fn drop(&mut self) {
+        self.shared.shutdown.store(true, Ordering::Release);
+        if let Some(worker) = self.worker.take() {
+            worker.thread().unpark();
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serial_test::parallel;
+
+    use super::super::Shishua;
+    use super::*;
+
+    const SEED: [u8; 32] = [
+        1, 0, 52, 0, 0, 0, 0, 0, 1, 0, 10, 0, 22, 32, 0, 0, 2, 0, 55, 49, 0, 11, 0, 0, 3, 0, 0, 0, 0, 0, 2, 92,
+    ];
+
+    #[test]
+    #[parallel]
+    fn same_stream() {
+        let mut rng = BackgroundShishua::<256>::from_seed(SEED);
+        let mut expected = Shishua::<256>::from_seed(SEED);
+
+        for _ in 0..1000 {
+            assert_eq!(rng.next_u32(), expected.next_u32());
+            assert_eq!(rng.next_u64(), expected.next_u64());
+            let (v, e) = (rng.next_m256i(), expected.next_m256i());
+            assert_eq!(unsafe { mem::transmute::<_, [u64; 4]>(v) }, unsafe {
+                mem::transmute::<_, [u64; 4]>(e)
+            });
+            assert_eq!(rng.buffer_index(), expected.buffer_index());
+        }
+
+        let mut bytes = [0u8; 256 * 3 + 100];
+        let mut expected_bytes = [0u8; 256 * 3 + 100];
+        rng.fill_bytes(&mut bytes);
+        expected.fill_bytes(&mut expected_bytes);
+        assert_eq!(bytes, expected_bytes);
+    }
+
+    #[test]
+    #[parallel]
+    fn same_stream_default_buffer_size() {
+        let mut rng = BackgroundShishua::<DEFAULT_BUFFER_SIZE>::from_seed(SEED);
+        let mut expected = Shishua::<DEFAULT_BUFFER_SIZE>::from_seed(SEED);
+
+        for _ in 0..(DEFAULT_BUFFER_SIZE / 8) * 5 {
+            assert_eq!(rng.next_u64(), expected.next_u64());
+        }
+    }
+
+    #[test]
+    #[parallel]
+    fn inline_generation() {
+        // Without a worker every buffer after the first is generated inline by the consumer
+        let shared = Arc::new(Shared::<256>::try_new(ShishuaCore::from_seed(SEED)).unwrap());
+        shared.generate(0);
+        let buffer = shared.slot(0);
+        let mut rng = BackgroundShishua {
+            shared,
+            worker: None,
+            generation: 0,
+            buffer,
+            buffer_index: 0,
+        };
+        let mut expected = Shishua::<256>::from_seed(SEED);
+
+        for _ in 0..1000 {
+            assert_eq!(rng.next_u64(), expected.next_u64());
+        }
+    }
+
+    #[test]
+    #[parallel]
+    fn construction_invalid_size() {
+        let result = BackgroundShishua::<1000>::try_from_seed(SEED);
+        assert_eq!(result.err(), Some(ShishuaError::BufferSizeNotPowerOfTwo(1000)));
+        let result = BackgroundShishua::<128>::try_from_seed(SEED);
+        assert_eq!(result.err(), Some(ShishuaError::BufferSizeTooSmall(128)));
+    }
+
+    #[test]
+    #[parallel]
+    fn drop_stops_worker() {
+        // Drop while the worker is parked on full slots, and right after construction
+        let mut rng = BackgroundShishua::<256>::from_seed(SEED);
+        rng.next_u64();
+        drop(rng);
+
+        drop(BackgroundShishua::<256>::from_seed(SEED));
+    }
+
+    #[test]
+    #[parallel]
+    fn move_across_threads() {
+        let mut rng = BackgroundShishua::<256>::from_seed(SEED);
+        let mut expected = Shishua::<256>::from_seed(SEED);
+        rng.next_u64();
+        expected.next_u64();
+
+        let values = thread::spawn(move || (0..1000).map(|_| rng.next_u64()).collect::<Vec<_>>())
+            .join()
+            .unwrap();
+        for v in values {
+            assert_eq!(v, expected.next_u64());
+        }
+    }
+}
@@ -3,12 +3,16 @@ use std::{
     mem::{self, transmute},
 };
 
+pub use background::*;
+pub use reseeding::*;
 pub use shishua::*;
 pub use simdrand::*;
 pub use vecs::*;
 pub use xoshiro256plus::*;
 pub use xoshiro256plusplus::*;
 
+mod background;
+mod reseeding;
 mod shishua;
 mod simdrand;
 mod vecs;
@@ -29,6 +33,27 @@ fn read_u64_into_vec(src: &[u8]) -> __m256i {
     }
 }
 
+/// Writes lanes into seed bytes, the inverse of [`read_u64_into_vec`] which loads the last lane first.
+fn write_u64_lanes_into_seed(lanes: [u64; 4], dst: &mut [u8]) {
+    for (chunk, lane) in dst.chunks_exact_mut(mem::size_of::<u64>()).zip(lanes.into_iter().rev()) {
+        chunk.copy_from_slice(&lane.to_le_bytes());
+    }
+}
+
+/// Loads lanes into a vector, lane `i` of the vector being `lanes[i]`.
+#[inline(always)]
+fn u64_lanes_into_vec(lanes: [u64; 4]) -> __m256i {
+    unsafe { _mm256_loadu_si256(lanes.as_ptr() as *const __m256i) }
+}
+
+/// Stores the lanes of a vector, the inverse of [`u64_lanes_into_vec`].
+#[inline(always)]
+fn u64_lanes_from_vec(vector: __m256i) -> [u64; 4] {
+    let mut lanes = [0; 4];
+    unsafe { _mm256_storeu_si256(lanes.as_mut_ptr() as *mut __m256i, vector) };
+    lanes
+}
+
 #[inline(always)]
 #[cfg(all(target_arch = "x86_64", target_feature = "avx512f", target_feature = "avx512vl"))]
 fn rotate_left<const K: i32>(x: __m256i) -> __m256i {
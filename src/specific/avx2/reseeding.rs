@@ -0,0 +1,238 @@
+use std::alloc::{Allocator, Global};
+use std::arch::x86_64::*;
+use std::mem;
+
+use rand_core::RngCore;
+
+use super::shishua::{Shishua, DEFAULT_BUFFER_SIZE};
+use super::simdrand::*;
+
+/// Wrapper around [`Shishua`] which periodically reseeds it from an entropy source `R`,
+/// similar to `rand::rngs::adapter::ReseedingRng`. Useful for long-running services.
+///
+/// Every `threshold`-th rebuffer is replaced by a reseed from `R` (through [`Shishua::reseed`]),
+/// so each seed produces at most `threshold` buffers of `BUFFER_SIZE` bytes.
+/// A `threshold` of 0 disables automatic reseeding.
+///
+/// # Error handling
+///
+/// If the entropy source fails during an automatic reseed, the generator keeps generating
+/// from its current state and tries again after another `threshold` rebuffers.
+pub struct ReseedingShishua<R: RngCore, const BUFFER_SIZE: usize = DEFAULT_BUFFER_SIZE, A: Allocator = Global> {
+    rng: Shishua<BUFFER_SIZE, A>,
+    reseeder: R,
+    threshold: u64,
+    rebuffers: u64,
+}
+
+impl<R: RngCore, const BUFFER_SIZE: usize, A: Allocator> ReseedingShishua<R, BUFFER_SIZE, A> {
+    /// Wraps `rng`, reseeding it from `reseeder` after every `threshold` rebuffers.
+    /// The buffer `rng` currently holds counts as the first one.
+    pub fn new(rng: Shishua<BUFFER_SIZE, A>, threshold: u64, reseeder: R) -> Self {
+        Self {
+            rng,
+            reseeder,
+            threshold,
+            rebuffers: 0,
+        }
+    }
+
+    /// Immediately reseeds the generator from the entropy source, discarding the rest of the buffer.
+    pub fn reseed(&mut self) -> Result<(), rand_core::Error> {
+        let mut seed = [0u8; 32];
+        self.reseeder.try_fill_bytes(&mut seed)?;
+        self.rng.reseed(seed);
+        self.rebuffers = 0;
+        Ok(())
+    }
+
+    #[inline(always)] // Same as the inner generator, this branch is checked every time we sample
+    fn ensure_buffered(&mut self, size: usize) {
+        if BUFFER_SIZE - self.rng.buffer_index() < size {
+            self.before_rebuffer();
+        }
+    }
+
+    #[cold]
+    #[inline(never)]
+    fn before_rebuffer(&mut self) {
+        if self.threshold == 0 {
+            return;
+        }
+
+        self.rebuffers += 1;
+        if self.rebuffers >= self.threshold {
+            // If reseeding fails the inner generator just rebuffers as usual
+            self.rebuffers = 0;
+            let _ = self.reseed();
+        }
+    }
+}
+
+impl<R: RngCore, const BUFFER_SIZE: usize, A: Allocator> SimdRand for ReseedingShishua<R, BUFFER_SIZE, A> {
+    #[inline(always)]
+    fn next_m256i(&mut self) -> __m256i {
+        self.ensure_buffered(mem::size_of::<__m256i>());
+        self.rng.next_m256i()
+    }
+}
+
+impl<R: RngCore, const BUFFER_SIZE: usize, A: Allocator> RngCore for ReseedingShishua<R, BUFFER_SIZE, A> {
+    #[inline(always)]
+    fn next_u32(&mut self) -> u32 {
+        self.ensure_buffered(mem::size_of::<u32>());
+        self.rng.next_u32()
+    }
+
+    #[inline(always)]
+    fn next_u64(&mut self) -> u64 {
+        self.ensure_buffered(mem::size_of::<u64>());
+        self.rng.next_u64()
+    }
+
+    #[inline(always)]
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        // Mirrors how the inner generator serves large requests, a buffer at a time
+        for chunk in dest.chunks_mut(BUFFER_SIZE) {
+            self.ensure_buffered(chunk.len());
+            self.rng.fill_bytes(chunk);
+        }
+    }
+
+    #[inline(always)]
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU32;
+
+    use rand_core::SeedableRng;
+    use rand_xoshiro::Xoshiro256PlusPlus;
+    use serial_test::parallel;
+
+    use super::*;
+
+    const BUFFER_SIZE: usize = 256;
+    const U64S_PER_BUFFER: usize = BUFFER_SIZE / mem::size_of::<u64>();
+    const SEED: [u8; 32] = [
+        1, 0, 52, 0, 0, 0, 0, 0, 1, 0, 10, 0, 22, 32, 0, 0, 2, 0, 55, 49, 0, 11, 0, 0, 3, 0, 0, 0, 0, 0, 2, 92,
+    ];
+
+    struct FailingRng;
+
+    // Only `try_fill_bytes` is used for reseeding, the infallible methods would have to panic anyway
+    impl RngCore for FailingRng {
+        fn next_u32(&mut self) -> u32 {
+            panic!("FailingRng has no entropy to give")
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            panic!("FailingRng has no entropy to give")
+        }
+
+        fn fill_bytes(&mut self, _: &mut [u8]) {
+            panic!("FailingRng has no entropy to give")
+        }
+
+        fn try_fill_bytes(&mut self, _: &mut [u8]) -> Result<(), rand_core::Error> {
+            Err(NonZeroU32::new(rand_core::Error::CUSTOM_START).unwrap().into())
+        }
+    }
+
+    #[test]
+    #[parallel]
+    fn reseeds_after_threshold() {
+        const THRESHOLD: u64 = 3;
+
+        let mut rng = ReseedingShishua::new(
+            Shishua::<BUFFER_SIZE>::from_seed(SEED),
+            THRESHOLD,
+            Xoshiro256PlusPlus::seed_from_u64(0),
+        );
+
+        let mut reseeder = Xoshiro256PlusPlus::seed_from_u64(0);
+        let mut seed = SEED;
+        for _ in 0..4 {
+            let mut expected = Shishua::<BUFFER_SIZE>::from_seed(seed);
+            for _ in 0..U64S_PER_BUFFER * THRESHOLD as usize {
+                assert_eq!(rng.next_u64(), expected.next_u64());
+            }
+            reseeder.fill_bytes(&mut seed);
+        }
+    }
+
+    #[test]
+    #[parallel]
+    fn reseeds_within_fill_bytes() {
+        const THRESHOLD: u64 = 2;
+
+        let mut rng = ReseedingShishua::new(
+            Shishua::<BUFFER_SIZE>::from_seed(SEED),
+            THRESHOLD,
+            Xoshiro256PlusPlus::seed_from_u64(0),
+        );
+
+        let mut bytes = [0u8; BUFFER_SIZE * 4];
+        rng.fill_bytes(&mut bytes);
+
+        let mut reseeder = Xoshiro256PlusPlus::seed_from_u64(0);
+        let mut seed = SEED;
+        for chunk in bytes.chunks(BUFFER_SIZE * THRESHOLD as usize) {
+            let mut expected_chunk = [0u8; BUFFER_SIZE * THRESHOLD as usize];
+            let mut expected = Shishua::<BUFFER_SIZE>::from_seed(seed);
+            expected.fill_bytes(&mut expected_chunk);
+            assert_eq!(chunk, &expected_chunk[..]);
+            reseeder.fill_bytes(&mut seed);
+        }
+    }
+
+    #[test]
+    #[parallel]
+    fn manual_reseed() {
+        let mut rng = ReseedingShishua::new(
+            Shishua::<BUFFER_SIZE>::from_seed(SEED),
+            0,
+            Xoshiro256PlusPlus::seed_from_u64(0),
+        );
+        rng.next_u32();
+        rng.reseed().unwrap();
+
+        let mut seed = [0u8; 32];
+        Xoshiro256PlusPlus::seed_from_u64(0).fill_bytes(&mut seed);
+        let mut expected = Shishua::<BUFFER_SIZE>::from_seed(seed);
+        for _ in 0..U64S_PER_BUFFER * 2 {
+            assert_eq!(rng.next_u64(), expected.next_u64());
+        }
+    }
+
+    #[test]
+    #[parallel]
+    fn zero_threshold_never_reseeds() {
+        let mut rng = ReseedingShishua::new(
+            Shishua::<BUFFER_SIZE>::from_seed(SEED),
+            0,
+            Xoshiro256PlusPlus::seed_from_u64(0),
+        );
+        let mut expected = Shishua::<BUFFER_SIZE>::from_seed(SEED);
+
+        for _ in 0..U64S_PER_BUFFER * 10 {
+            assert_eq!(rng.next_u64(), expected.next_u64());
+        }
+    }
+
+    #[test]
+    #[parallel]
+    fn failing_reseeder_continues_stream() {
+        let mut rng = ReseedingShishua::new(Shishua::<BUFFER_SIZE>::from_seed(SEED), 1, FailingRng);
+        let mut expected = Shishua::<BUFFER_SIZE>::from_seed(SEED);
+
+        assert!(rng.reseed().is_err());
+        for _ in 0..U64S_PER_BUFFER * 10 {
+            assert_eq!(rng.next_u64(), expected.next_u64());
+        }
+    }
+}
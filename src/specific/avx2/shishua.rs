@@ -1,14 +1,20 @@
+use std::alloc::{AllocError, Allocator, Global, Layout, LayoutError};
+use std::marker::PhantomData;
 use std::mem::transmute;
+use std::path::Path;
 use std::ptr::NonNull;
+use std::slice;
 use std::{arch::x86_64::*, mem::size_of};
+use std::{fmt, fs};
 
+use rand_core::block::BlockRngCore;
 use rand_core::le::read_u64_into;
 use rand_core::{RngCore, SeedableRng};
-use std::alloc::{Layout, LayoutError};
 use std::iter::Iterator;
 use std::{alloc, mem};
 
 use super::simdrand::*;
+use crate::seeding::{split_seeds, SplittableRng};
 
 pub const DEFAULT_BUFFER_SIZE: usize = 1024 * 32;
 
@@ -17,6 +23,10 @@ pub const DEFAULT_BUFFER_SIZE: usize = 1024 * 32;
 /// When sampling, if enough randomness is buffered, it will just extract your T from the buffered bytes.
 /// When all the buffered randomness is spent, it will rebuffer using vectorized instructions
 ///
+/// The buffered state is allocated through `A` (the global allocator by default).
+/// Use [`Shishua::try_from_seed_in`] to plug in a different allocator (arenas, huge pages etc)
+/// and to handle invalid buffer sizes or allocation failure without panicking.
+///
 /// # Performance
 ///
 /// Performance varies slightly by what `BUFFER_SIZE` is set.
@@ -25,6 +35,13 @@ pub const DEFAULT_BUFFER_SIZE: usize = 1024 * 32;
 /// so I recommend you benchmark your specific workload and use something like
 /// perf stat to observe cache misses (L1-dcache-load-misses:u).
 ///
+/// # Thread safety
+///
+/// `Shishua` uniquely owns its buffered state, so it is `Send` (if the allocator is),
+/// which means a seeded generator can be moved into worker threads.
+/// It is also `Sync` (if the allocator is), as there is no interior mutability -
+/// all sampling requires `&mut self`.
+///
 /// # Safety
 ///
 /// As this is a performance-oriented library, there is some unsafe code here.
@@ -33,42 +50,850 @@ pub const DEFAULT_BUFFER_SIZE: usize = 1024 * 32;
 /// provide feedback.
 ///
 
-pub struct Shishua<const BUFFER_SIZE: usize = DEFAULT_BUFFER_SIZE> {
-    state: NonNull<BufferedState<BUFFER_SIZE>>,
+pub struct Shishua<const BUFFER_SIZE: usize = DEFAULT_BUFFER_SIZE, A: Allocator = Global> {
+    state: NonNull<BufferedState<[u8; BUFFER_SIZE]>>,
+    alloc: A,
+}
+
+/// Heap-free variant of [`Shishua`].
+/// The buffered state is stored by value (aligned to 128 bytes), so it can live on the stack,
+/// in a static or anywhere else allocation is not allowed.
+/// Produces the exact same output as [`Shishua`] for the same seed and `BUFFER_SIZE`.
+///
+/// Keep in mind that the struct is larger than `BUFFER_SIZE` bytes,
+/// so moving it around is not free.
+#[derive(Clone)]
+#[repr(align(128))]
+pub struct ShishuaInline<const BUFFER_SIZE: usize = DEFAULT_BUFFER_SIZE> {
+    state: BufferedState<[u8; BUFFER_SIZE]>,
+}
+
+/// Variant of [`Shishua`] where the buffer size is chosen at construction instead of at compile time.
+/// Useful when the same binary is deployed to machines with very different cache sizes,
+/// [`Shishua::auto`] (or [`ShishuaDyn::auto`]) picks a buffer size based on the data cache sizes of the machine,
+/// while `from_seed` uses [`DEFAULT_BUFFER_SIZE`] so that the output doesn't depend on the machine.
+/// Produces the exact same output as [`Shishua`] for the same seed and buffer size.
+///
+/// This is a separate type because the buffer size of [`Shishua`] is a const generic parameter,
+/// so it can't be changed at runtime without changing the type. Here the buffer is an unsized `[u8]`
+/// at the end of the allocation instead, and its length is carried in the pointer.
+/// The fixed size variants are slightly faster, as the buffer size is a constant in the hot path.
+pub struct ShishuaDyn<A: Allocator = Global> {
+    state: NonNull<BufferedState<[u8]>>,
+    alloc: A,
+}
+
+/// Variant of [`Shishua`] which never rebuffers all at once.
+/// Instead the next buffer is generated in place, `STEPS_PER_DRAW` 128-byte steps at a time on each draw,
+/// into the parts of the buffer that have already been consumed.
+/// This spreads the cost of rebuffering evenly across draws, for when a full rebuffer is a visible latency spike.
+/// Produces the exact same output as [`Shishua`] for the same seed and `BUFFER_SIZE`.
+///
+/// A step can only be generated once the 128 bytes it overwrites have been consumed, so the default of one step
+/// per draw keeps up with any draw of up to 128 bytes. Larger draws (or draws that skip the end of the buffer)
+/// leave steps behind, which are then generated when the buffer runs out, same as a regular rebuffer.
+pub struct ShishuaIncremental<const BUFFER_SIZE: usize = DEFAULT_BUFFER_SIZE, const STEPS_PER_DRAW: usize = 1> {
+    rng: Shishua<BUFFER_SIZE>,
+    // The state that the next buffer is being generated from
+    next_buffer_state: RawState,
+    // Number of 128-byte steps of the next buffer generated so far
+    steps: usize,
+}
+
+/// Errors that can occur when constructing a [`Shishua`] generator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShishuaError {
+    /// `BUFFER_SIZE` is not a power of 2
+    BufferSizeNotPowerOfTwo(usize),
+    /// `BUFFER_SIZE` is smaller than the 256 byte minimum
+    BufferSizeTooSmall(usize),
+    /// The memory layout of the buffered state could not be constructed
+    Layout(LayoutError),
+    /// The allocator could not allocate the buffered state
+    Alloc(AllocError),
+    /// The snapshot was taken with a different `BUFFER_SIZE` or is corrupt
+    InvalidSnapshot,
+}
+
+impl fmt::Display for ShishuaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShishuaError::BufferSizeNotPowerOfTwo(size) => {
+                write!(f, "The Shishua buffer size must be a power of 2, was {size}")
+            }
+            ShishuaError::BufferSizeTooSmall(size) => {
+                write!(f, "The Shishua buffer size must be >= 256 (and power of 2), was {size}")
+            }
+            ShishuaError::Layout(_) => write!(f, "couldnt construct memory layout of Shishua buffered state"),
+            ShishuaError::Alloc(_) => write!(f, "Failed to allocate state for Shishua"),
+            ShishuaError::InvalidSnapshot => write!(f, "Invalid Shishua snapshot"),
+        }
+    }
+}
+
+impl std::error::Error for ShishuaError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ShishuaError::Layout(e) => Some(e),
+            ShishuaError::Alloc(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+const BUFFERED_STATE_ALIGNMENT: usize = 128;
+const fn get_buffered_state_layout_unchecked<const BUFFER_SIZE: usize>() -> Layout {
+    unsafe {
+        Layout::from_size_align_unchecked(size_of::<BufferedState<[u8; BUFFER_SIZE]>>(), BUFFERED_STATE_ALIGNMENT)
+    }
+}
+const fn get_buffered_state_layout<const BUFFER_SIZE: usize>() -> Result<Layout, LayoutError> {
+    Layout::from_size_align(size_of::<BufferedState<[u8; BUFFER_SIZE]>>(), BUFFERED_STATE_ALIGNMENT)
+}
+
+fn get_dyn_buffered_state_layout(buffer_size: usize) -> Result<Layout, LayoutError> {
+    // Same layout as `BufferedState<[u8; buffer_size]>`, the buffer comes right after the other fields
+    let (layout, _) = Layout::new::<BufferedState<[u8; 0]>>().extend(Layout::array::<u8>(buffer_size)?)?;
+    layout.align_to(BUFFERED_STATE_ALIGNMENT)
+}
+
+fn validate_buffer_size<const BUFFER_SIZE: usize>() -> Result<Layout, ShishuaError> {
+    let layout = get_buffered_state_layout::<BUFFER_SIZE>().map_err(ShishuaError::Layout)?;
+    check_buffer_size(BUFFER_SIZE)?;
+    Ok(layout)
+}
+
+fn validate_dyn_buffer_size(buffer_size: usize) -> Result<Layout, ShishuaError> {
+    let layout = get_dyn_buffered_state_layout(buffer_size).map_err(ShishuaError::Layout)?;
+    check_buffer_size(buffer_size)?;
+    Ok(layout)
+}
+
+pub(super) fn check_buffer_size(buffer_size: usize) -> Result<(), ShishuaError> {
+    if !buffer_size.is_power_of_two() {
+        return Err(ShishuaError::BufferSizeNotPowerOfTwo(buffer_size));
+    }
+    if buffer_size < 256 {
+        return Err(ShishuaError::BufferSizeTooSmall(buffer_size));
+    }
+    Ok(())
+}
+
+fn assert_buffer_size<const BUFFER_SIZE: usize>() {
+    if let Err(e) = validate_buffer_size::<BUFFER_SIZE>() {
+        panic!("{e}");
+    }
+}
+
+impl<const BUFFER_SIZE: usize, A: Allocator> Shishua<BUFFER_SIZE, A> {
+    pub const LAYOUT: Layout = get_buffered_state_layout_unchecked::<BUFFER_SIZE>();
+
+    /// Fallible construction of the generator, allocating the buffered state through `alloc`.
+    ///
+    /// Returns an error instead of panicking if `BUFFER_SIZE` is invalid
+    /// (it must be a power of 2 and at least 256) or if the allocation fails.
+    pub fn try_from_seed_in(seed: [u8; 32], alloc: A) -> Result<Self, ShishuaError> {
+        let mut rng = Self::try_allocate_in(alloc)?;
+        unsafe { rng.state.as_mut().init(&seed) };
+        Ok(rng)
+    }
+
+    /// Fallible construction of a generator from a snapshot, allocating the buffered state through `alloc`.
+    pub fn try_from_snapshot_in(snapshot: &ShishuaSnapshot<BUFFER_SIZE>, alloc: A) -> Result<Self, ShishuaError> {
+        let mut rng = Self::try_allocate_in(alloc)?;
+        rng.restore(snapshot);
+        Ok(rng)
+    }
+
+    /// Allocates the buffered state, the caller is responsible for initializing it.
+    fn try_allocate_in(alloc: A) -> Result<Self, ShishuaError> {
+        let layout = validate_buffer_size::<BUFFER_SIZE>()?;
+
+        let ptr = alloc.allocate(layout).map_err(ShishuaError::Alloc)?;
+        let ptr = ptr.cast::<BufferedState<[u8; BUFFER_SIZE]>>();
+
+        Ok(Self { state: ptr, alloc })
+    }
+
+    /// Takes a compact snapshot of the generator which can later be restored
+    /// to produce the exact same continuation.
+    pub fn snapshot(&self) -> ShishuaSnapshot<BUFFER_SIZE> {
+        unsafe { self.state.as_ref().snapshot() }
+    }
+
+    /// Restores the generator to the point the snapshot was taken, regenerating the buffer.
+    pub fn restore(&mut self, snapshot: &ShishuaSnapshot<BUFFER_SIZE>) {
+        unsafe { self.state.as_mut().restore(snapshot) }
+    }
+
+    #[inline(always)]
+    pub fn buffer_index(&self) -> usize {
+        let state = unsafe { self.state.as_ref() };
+        state.buffer_index
+    }
+
+    #[inline(always)]
+    pub fn next_f32(&mut self) -> f32 {
+        let v = self.next_u32();
+        (v >> 8) as f32 * (1.0f32 / (1u32 << 24) as f32)
+    }
+
+    #[inline(always)]
+    pub fn next_f64(&mut self) -> f64 {
+        let v = self.next_u64();
+        (v >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+
+    /// Fills `dest` with random u64's, equivalent to calling `next_u64` for each element
+    /// but copying straight from the buffer.
+    pub fn fill_u64(&mut self, dest: &mut [u64]) {
+        unsafe { self.state.as_mut().fill_u64(dest) }
+    }
+
+    /// Fills `dest` with random f64's in the range [0, 1), equivalent to calling `next_f64` for each element
+    /// but converting straight from the buffer using AVX2.
+    pub fn fill_f64(&mut self, dest: &mut [f64]) {
+        unsafe { self.state.as_mut().fill_f64(dest) }
+    }
+
+    /// Fills `dest` with random u32's, equivalent to calling `next_u32` for each element
+    /// but copying straight from the buffer.
+    pub fn fill_u32(&mut self, dest: &mut [u32]) {
+        unsafe { self.state.as_mut().fill_u32(dest) }
+    }
+
+    /// Fills `dest` with random f32's in the range [0, 1), equivalent to calling `next_f32` for each element
+    /// but converting straight from the buffer using AVX2.
+    pub fn fill_f32(&mut self, dest: &mut [f32]) {
+        unsafe { self.state.as_mut().fill_f32(dest) }
+    }
+
+    /// Skips `n_bytes` of output, producing the same continuation as if the bytes had been
+    /// read one at a time. Whole buffers are skipped by advancing the state without writing any output.
+    pub fn discard(&mut self, n_bytes: u64) {
+        unsafe { self.state.as_mut().discard(n_bytes) }
+    }
+
+    /// Reseeds the generator in place, reusing the existing buffer.
+    /// The generator will produce the same output as one created through `from_seed` with the same seed.
+    pub fn reseed(&mut self, seed: [u8; 32]) {
+        unsafe { self.state.as_mut().init(&seed) }
+    }
+}
+
+impl<const BUFFER_SIZE: usize, A: Allocator> SimdRand for Shishua<BUFFER_SIZE, A> {
+    #[inline(always)]
+    fn next_m256i(&mut self) -> __m256i {
+        unsafe { self.state.as_mut().next_m256i() }
+    }
+
+    /// Copies straight from the buffer like [`Shishua::fill_u64`], so no values are discarded.
+    #[inline(always)]
+    fn fill_u64(&mut self, dest: &mut [u64]) {
+        Self::fill_u64(self, dest)
+    }
+
+    /// Converts straight from the buffer like [`Shishua::fill_f64`], so no values are discarded.
+    #[inline(always)]
+    fn fill_f64(&mut self, dest: &mut [f64]) {
+        Self::fill_f64(self, dest)
+    }
+
+    /// Copies straight from the buffer like [`Shishua::fill_u32`], so no values are discarded.
+    #[inline(always)]
+    fn fill_u32(&mut self, dest: &mut [u32]) {
+        Self::fill_u32(self, dest)
+    }
+
+    /// Converts straight from the buffer like [`Shishua::fill_f32`], so no values are discarded.
+    #[inline(always)]
+    fn fill_f32(&mut self, dest: &mut [f32]) {
+        Self::fill_f32(self, dest)
+    }
+}
+
+impl<const BUFFER_SIZE: usize> SeedableRng for Shishua<BUFFER_SIZE> {
+    type Seed = [u8; 32];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        match Self::try_from_seed_in(seed, Global) {
+            Ok(rng) => rng,
+            Err(ShishuaError::Alloc(_)) => alloc::handle_alloc_error(Self::LAYOUT),
+            Err(e) => panic!("{e}"),
+        }
+    }
+}
+
+impl<const BUFFER_SIZE: usize> Shishua<BUFFER_SIZE> {
+    pub fn from_snapshot(snapshot: &ShishuaSnapshot<BUFFER_SIZE>) -> Self {
+        match Self::try_from_snapshot_in(snapshot, Global) {
+            Ok(rng) => rng,
+            Err(ShishuaError::Alloc(_)) => alloc::handle_alloc_error(Self::LAYOUT),
+            Err(e) => panic!("{e}"),
+        }
+    }
+}
+
+impl Shishua {
+    /// Constructs a [`ShishuaDyn`] with a buffer size picked for the caches of the machine, see [`ShishuaDyn::auto`].
+    /// The buffer size of `Shishua` itself is fixed at compile time, so this returns the runtime sized variant.
+    pub fn auto(seed: [u8; 32]) -> ShishuaDyn {
+        ShishuaDyn::auto(seed)
+    }
+}
+
+impl<const BUFFER_SIZE: usize, A: Allocator + Clone> Clone for Shishua<BUFFER_SIZE, A> {
+    /// Deep copy of the generator, including the buffer and position in it.
+    /// The clone will produce the exact same continuation as the original.
+    fn clone(&self) -> Self {
+        let rng = match Self::try_allocate_in(self.alloc.clone()) {
+            Ok(rng) => rng,
+            Err(_) => alloc::handle_alloc_error(Self::LAYOUT),
+        };
+        unsafe {
+            std::ptr::copy_nonoverlapping(self.state.as_ptr(), rng.state.as_ptr(), 1);
+        }
+        rng
+    }
+}
+
+impl<const BUFFER_SIZE: usize, A: Allocator + Clone> SplittableRng for Shishua<BUFFER_SIZE, A> {
+    /// Splits by key derivation: draws a 32 byte key from the generator,
+    /// and reseeds the halves with seeds derived from the key (see [`crate::seeding`]).
+    /// The right half has the same buffer size as the left.
+    fn split(mut self) -> (Self, Self) {
+        let (left, right) = split_seeds(&mut self);
+        let other = match Self::try_from_seed_in(right, self.alloc.clone()) {
+            Ok(rng) => rng,
+            Err(ShishuaError::Alloc(_)) => alloc::handle_alloc_error(Self::LAYOUT),
+            Err(e) => panic!("{e}"),
+        };
+        self.reseed(left);
+        (self, other)
+    }
+}
+
+// SAFETY: the buffered state is uniquely owned by the generator (like a Box),
+// so moving it to another thread is fine as long as the allocator can be moved as well
+unsafe impl<const BUFFER_SIZE: usize, A: Allocator + Send> Send for Shishua<BUFFER_SIZE, A> {}
+
+// SAFETY: there is no interior mutability, the buffered state is only mutated through &mut self
+unsafe impl<const BUFFER_SIZE: usize, A: Allocator + Sync> Sync for Shishua<BUFFER_SIZE, A> {}
+
+impl<const BUFFER_SIZE: usize, A: Allocator> Drop for Shishua<BUFFER_SIZE, A> {
+    fn drop(&mut self) {
+        unsafe {
+            self.alloc.deallocate(self.state.cast(), Self::LAYOUT);
+        }
+    }
+}
+
+impl<const BUFFER_SIZE: usize, A: Allocator> RngCore for Shishua<BUFFER_SIZE, A> {
+    #[inline(always)]
+    fn next_u32(&mut self) -> u32 {
+        unsafe { self.state.as_mut().next_u32() }
+    }
+
+    #[inline(always)]
+    fn next_u64(&mut self) -> u64 {
+        unsafe { self.state.as_mut().next_u64() }
+    }
+
+    #[inline(always)]
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        unsafe { self.state.as_mut().fill_bytes(dest) }
+    }
+
+    #[inline(always)]
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl<const BUFFER_SIZE: usize> ShishuaInline<BUFFER_SIZE> {
+    pub fn from_snapshot(snapshot: &ShishuaSnapshot<BUFFER_SIZE>) -> Self {
+        assert_buffer_size::<BUFFER_SIZE>();
+
+        let mut rng: Self = unsafe { mem::zeroed() };
+        rng.restore(snapshot);
+        rng
+    }
+
+    /// Takes a compact snapshot of the generator which can later be restored
+    /// to produce the exact same continuation.
+    pub fn snapshot(&self) -> ShishuaSnapshot<BUFFER_SIZE> {
+        self.state.snapshot()
+    }
+
+    /// Restores the generator to the point the snapshot was taken, regenerating the buffer.
+    pub fn restore(&mut self, snapshot: &ShishuaSnapshot<BUFFER_SIZE>) {
+        self.state.restore(snapshot)
+    }
+
+    #[inline(always)]
+    pub fn buffer_index(&self) -> usize {
+        self.state.buffer_index
+    }
+
+    #[inline(always)]
+    pub fn next_f32(&mut self) -> f32 {
+        let v = self.next_u32();
+        (v >> 8) as f32 * (1.0f32 / (1u32 << 24) as f32)
+    }
+
+    #[inline(always)]
+    pub fn next_f64(&mut self) -> f64 {
+        let v = self.next_u64();
+        (v >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+
+    /// Fills `dest` with random u64's, equivalent to calling `next_u64` for each element
+    /// but copying straight from the buffer.
+    pub fn fill_u64(&mut self, dest: &mut [u64]) {
+        self.state.fill_u64(dest)
+    }
+
+    /// Fills `dest` with random f64's in the range [0, 1), equivalent to calling `next_f64` for each element
+    /// but converting straight from the buffer using AVX2.
+    pub fn fill_f64(&mut self, dest: &mut [f64]) {
+        self.state.fill_f64(dest)
+    }
+
+    /// Fills `dest` with random u32's, equivalent to calling `next_u32` for each element
+    /// but copying straight from the buffer.
+    pub fn fill_u32(&mut self, dest: &mut [u32]) {
+        self.state.fill_u32(dest)
+    }
+
+    /// Fills `dest` with random f32's in the range [0, 1), equivalent to calling `next_f32` for each element
+    /// but converting straight from the buffer using AVX2.
+    pub fn fill_f32(&mut self, dest: &mut [f32]) {
+        self.state.fill_f32(dest)
+    }
+
+    /// Skips `n_bytes` of output, producing the same continuation as if the bytes had been
+    /// read one at a time. Whole buffers are skipped by advancing the state without writing any output.
+    pub fn discard(&mut self, n_bytes: u64) {
+        self.state.discard(n_bytes)
+    }
+
+    /// Reseeds the generator in place, reusing the existing buffer.
+    /// The generator will produce the same output as one created through `from_seed` with the same seed.
+    pub fn reseed(&mut self, seed: [u8; 32]) {
+        self.state.init(&seed)
+    }
+}
+
+impl<const BUFFER_SIZE: usize> SimdRand for ShishuaInline<BUFFER_SIZE> {
+    #[inline(always)]
+    fn next_m256i(&mut self) -> __m256i {
+        self.state.next_m256i()
+    }
+
+    /// Copies straight from the buffer like [`ShishuaInline::fill_u64`], so no values are discarded.
+    #[inline(always)]
+    fn fill_u64(&mut self, dest: &mut [u64]) {
+        Self::fill_u64(self, dest)
+    }
+
+    /// Converts straight from the buffer like [`ShishuaInline::fill_f64`], so no values are discarded.
+    #[inline(always)]
+    fn fill_f64(&mut self, dest: &mut [f64]) {
+        Self::fill_f64(self, dest)
+    }
+
+    /// Copies straight from the buffer like [`ShishuaInline::fill_u32`], so no values are discarded.
+    #[inline(always)]
+    fn fill_u32(&mut self, dest: &mut [u32]) {
+        Self::fill_u32(self, dest)
+    }
+
+    /// Converts straight from the buffer like [`ShishuaInline::fill_f32`], so no values are discarded.
+    #[inline(always)]
+    fn fill_f32(&mut self, dest: &mut [f32]) {
+        Self::fill_f32(self, dest)
+    }
+}
+
+impl<const BUFFER_SIZE: usize> SeedableRng for ShishuaInline<BUFFER_SIZE> {
+    type Seed = [u8; 32];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        assert_buffer_size::<BUFFER_SIZE>();
+
+        // All-zero is a valid bit pattern for every field,
+        // and all of it is overwritten by init below anyway
+        let mut rng: Self = unsafe { mem::zeroed() };
+        rng.state.init(&seed);
+        rng
+    }
+}
+
+impl<const BUFFER_SIZE: usize> SplittableRng for ShishuaInline<BUFFER_SIZE> {
+    /// Splits by key derivation: draws a 32 byte key from the generator,
+    /// and reseeds the halves with seeds derived from the key (see [`crate::seeding`]).
+    /// The right half has the same buffer size as the left.
+    fn split(mut self) -> (Self, Self) {
+        let (left, right) = split_seeds(&mut self);
+        self.reseed(left);
+        (self, Self::from_seed(right))
+    }
+}
+
+impl<const BUFFER_SIZE: usize> RngCore for ShishuaInline<BUFFER_SIZE> {
+    #[inline(always)]
+    fn next_u32(&mut self) -> u32 {
+        self.state.next_u32()
+    }
+
+    #[inline(always)]
+    fn next_u64(&mut self) -> u64 {
+        self.state.next_u64()
+    }
+
+    #[inline(always)]
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.state.fill_bytes(dest)
+    }
+
+    #[inline(always)]
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl<A: Allocator> ShishuaDyn<A> {
+    /// Fallible construction of the generator with a buffer of `buffer_size` bytes,
+    /// allocating the buffered state through `alloc`.
+    ///
+    /// Returns an error instead of panicking if `buffer_size` is invalid
+    /// (it must be a power of 2 and at least 256) or if the allocation fails.
+    pub fn try_from_seed_in(seed: [u8; 32], buffer_size: usize, alloc: A) -> Result<Self, ShishuaError> {
+        let mut rng = Self::try_allocate_in(buffer_size, alloc)?;
+        unsafe { rng.state.as_mut().init(&seed) };
+        Ok(rng)
+    }
+
+    /// Allocates the buffered state, the caller is responsible for initializing it.
+    fn try_allocate_in(buffer_size: usize, alloc: A) -> Result<Self, ShishuaError> {
+        let layout = validate_dyn_buffer_size(buffer_size)?;
+
+        let ptr = alloc.allocate(layout).map_err(ShishuaError::Alloc)?;
+        // The length of the buffer is carried as the metadata of the pointer
+        let ptr = NonNull::slice_from_raw_parts(ptr.cast::<u8>(), buffer_size);
+        let ptr = unsafe { NonNull::new_unchecked(ptr.as_ptr() as *mut BufferedState<[u8]>) };
+
+        Ok(Self { state: ptr, alloc })
+    }
+
+    fn layout(&self) -> Layout {
+        // Validated on construction
+        get_dyn_buffered_state_layout(self.buffer_size()).unwrap()
+    }
+
+    #[inline(always)]
+    pub fn buffer_size(&self) -> usize {
+        let state = unsafe { self.state.as_ref() };
+        state.buffer_size()
+    }
+
+    #[inline(always)]
+    pub fn buffer_index(&self) -> usize {
+        let state = unsafe { self.state.as_ref() };
+        state.buffer_index
+    }
+
+    #[inline(always)]
+    pub fn next_f32(&mut self) -> f32 {
+        let v = self.next_u32();
+        (v >> 8) as f32 * (1.0f32 / (1u32 << 24) as f32)
+    }
+
+    #[inline(always)]
+    pub fn next_f64(&mut self) -> f64 {
+        let v = self.next_u64();
+        (v >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+
+    /// Fills `dest` with random u64's, equivalent to calling `next_u64` for each element
+    /// but copying straight from the buffer.
+    pub fn fill_u64(&mut self, dest: &mut [u64]) {
+        unsafe { self.state.as_mut().fill_u64(dest) }
+    }
+
+    /// Fills `dest` with random f64's in the range [0, 1), equivalent to calling `next_f64` for each element
+    /// but converting straight from the buffer using AVX2.
+    pub fn fill_f64(&mut self, dest: &mut [f64]) {
+        unsafe { self.state.as_mut().fill_f64(dest) }
+    }
+
+    /// Fills `dest` with random u32's, equivalent to calling `next_u32` for each element
+    /// but copying straight from the buffer.
+    pub fn fill_u32(&mut self, dest: &mut [u32]) {
+        unsafe { self.state.as_mut().fill_u32(dest) }
+    }
+
+    /// Fills `dest` with random f32's in the range [0, 1), equivalent to calling `next_f32` for each element
+    /// but converting straight from the buffer using AVX2.
+    pub fn fill_f32(&mut self, dest: &mut [f32]) {
+        unsafe { self.state.as_mut().fill_f32(dest) }
+    }
+
+    /// Skips `n_bytes` of output, producing the same continuation as if the bytes had been
+    /// read one at a time. Whole buffers are skipped by advancing the state without writing any output.
+    pub fn discard(&mut self, n_bytes: u64) {
+        unsafe { self.state.as_mut().discard(n_bytes) }
+    }
+
+    /// Reseeds the generator in place, reusing the existing buffer.
+    /// The generator will produce the same output as one created through `from_seed` with the same seed.
+    pub fn reseed(&mut self, seed: [u8; 32]) {
+        unsafe { self.state.as_mut().init(&seed) }
+    }
+}
+
+impl ShishuaDyn {
+    /// Constructs the generator with a buffer of `buffer_size` bytes,
+    /// e.g. to override the size picked by [`ShishuaDyn::auto`].
+    ///
+    /// # Panics
+    ///
+    /// If `buffer_size` is not a power of 2 or is smaller than 256.
+    pub fn from_seed_with_buffer_size(seed: [u8; 32], buffer_size: usize) -> Self {
+        match Self::try_from_seed_in(seed, buffer_size, Global) {
+            Ok(rng) => rng,
+            Err(ShishuaError::Alloc(_)) => {
+                alloc::handle_alloc_error(get_dyn_buffered_state_layout(buffer_size).unwrap())
+            }
+            Err(e) => panic!("{e}"),
+        }
+    }
+
+    /// Constructs the generator with a buffer size picked by [`auto_buffer_size`],
+    /// based on the data cache sizes of the machine.
+    /// The buffer size changes the output stream, so the same seed can produce different output on different machines,
+    /// use [`ShishuaDyn::from_seed_with_buffer_size`] where the output has to be reproducible.
+    pub fn auto(seed: [u8; 32]) -> Self {
+        Self::from_seed_with_buffer_size(seed, auto_buffer_size())
+    }
+}
+
+impl<A: Allocator> SimdRand for ShishuaDyn<A> {
+    #[inline(always)]
+    fn next_m256i(&mut self) -> __m256i {
+        unsafe { self.state.as_mut().next_m256i() }
+    }
+
+    /// Copies straight from the buffer like [`ShishuaDyn::fill_u64`], so no values are discarded.
+    #[inline(always)]
+    fn fill_u64(&mut self, dest: &mut [u64]) {
+        Self::fill_u64(self, dest)
+    }
+
+    /// Converts straight from the buffer like [`ShishuaDyn::fill_f64`], so no values are discarded.
+    #[inline(always)]
+    fn fill_f64(&mut self, dest: &mut [f64]) {
+        Self::fill_f64(self, dest)
+    }
+
+    /// Copies straight from the buffer like [`ShishuaDyn::fill_u32`], so no values are discarded.
+    #[inline(always)]
+    fn fill_u32(&mut self, dest: &mut [u32]) {
+        Self::fill_u32(self, dest)
+    }
+
+    /// Converts straight from the buffer like [`ShishuaDyn::fill_f32`], so no values are discarded.
+    #[inline(always)]
+    fn fill_f32(&mut self, dest: &mut [f32]) {
+        Self::fill_f32(self, dest)
+    }
+}
+
+impl SeedableRng for ShishuaDyn {
+    type Seed = [u8; 32];
+
+    /// Uses a buffer of [`DEFAULT_BUFFER_SIZE`] bytes, so the output only depends on the seed.
+    fn from_seed(seed: Self::Seed) -> Self {
+        Self::from_seed_with_buffer_size(seed, DEFAULT_BUFFER_SIZE)
+    }
+}
+
+impl<A: Allocator + Clone> Clone for ShishuaDyn<A> {
+    /// Deep copy of the generator, including the buffer and position in it.
+    /// The clone will produce the exact same continuation as the original.
+    fn clone(&self) -> Self {
+        let rng = match Self::try_allocate_in(self.buffer_size(), self.alloc.clone()) {
+            Ok(rng) => rng,
+            Err(_) => alloc::handle_alloc_error(self.layout()),
+        };
+        unsafe {
+            let size = self.layout().size();
+            std::ptr::copy_nonoverlapping(self.state.as_ptr() as *const u8, rng.state.as_ptr() as *mut u8, size);
+        }
+        rng
+    }
+}
+
+impl<A: Allocator + Clone> SplittableRng for ShishuaDyn<A> {
+    /// Splits by key derivation: draws a 32 byte key from the generator,
+    /// and reseeds the halves with seeds derived from the key (see [`crate::seeding`]).
+    /// The right half has the same buffer size as the left.
+    fn split(mut self) -> (Self, Self) {
+        let (left, right) = split_seeds(&mut self);
+        let other = match Self::try_from_seed_in(right, self.buffer_size(), self.alloc.clone()) {
+            Ok(rng) => rng,
+            Err(ShishuaError::Alloc(_)) => alloc::handle_alloc_error(self.layout()),
+            Err(e) => panic!("{e}"),
+        };
+        self.reseed(left);
+        (self, other)
+    }
+}
+
+// SAFETY: see the impls for Shishua, the buffered state is owned the same way
+unsafe impl<A: Allocator + Send> Send for ShishuaDyn<A> {}
+
+// SAFETY: see the impls for Shishua, the buffered state is owned the same way
+unsafe impl<A: Allocator + Sync> Sync for ShishuaDyn<A> {}
+
+impl<A: Allocator> Drop for ShishuaDyn<A> {
+    fn drop(&mut self) {
+        let layout = self.layout();
+        unsafe {
+            self.alloc.deallocate(self.state.cast(), layout);
+        }
+    }
+}
+
+impl<A: Allocator> RngCore for ShishuaDyn<A> {
+    #[inline(always)]
+    fn next_u32(&mut self) -> u32 {
+        unsafe { self.state.as_mut().next_u32() }
+    }
+
+    #[inline(always)]
+    fn next_u64(&mut self) -> u64 {
+        unsafe { self.state.as_mut().next_u64() }
+    }
+
+    #[inline(always)]
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        unsafe { self.state.as_mut().fill_bytes(dest) }
+    }
+
+    #[inline(always)]
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+const CPU_CACHE_PATH: &str = "/sys/devices/system/cpu/cpu0/cache";
+
+/// Picks a buffer size for [`ShishuaDyn`] based on the data cache sizes of the machine,
+/// as reported in `/sys/devices/system/cpu/cpu0/cache`.
+///
+/// The buffer is the largest power of 2 that fits in the L1 data cache.
+/// If the L1 size is unknown, 1/16th of L2 is used instead, and if neither is known `DEFAULT_BUFFER_SIZE`.
+pub fn auto_buffer_size() -> usize {
+    let (l1, l2) = read_data_cache_sizes(Path::new(CPU_CACHE_PATH));
+    buffer_size_for_caches(l1, l2)
+}
+
+fn buffer_size_for_caches(l1: Option<usize>, l2: Option<usize>) -> usize {
+    const MIN: usize = 256;
+
+    let size = match (l1, l2) {
+        (Some(l1), _) => l1,
+        (None, Some(l2)) => l2 / 16,
+        (None, None) => return DEFAULT_BUFFER_SIZE,
+    };
+    if size < MIN {
+        return MIN;
+    }
+
+    // Largest power of 2 <= size
+    1 << (usize::BITS - 1 - size.leading_zeros())
 }
 
-const BUFFERED_STATE_ALIGNMENT: usize = 128;
-const fn get_buffered_state_layout_unchecked<const BUFFER_SIZE: usize>() -> Layout {
-    unsafe { Layout::from_size_align_unchecked(size_of::<BufferedState<BUFFER_SIZE>>(), BUFFERED_STATE_ALIGNMENT) }
+/// Reads the sizes of the L1 and L2 data (or unified) caches from a sysfs cache directory.
+fn read_data_cache_sizes(dir: &Path) -> (Option<usize>, Option<usize>) {
+    let mut l1 = None;
+    let mut l2 = None;
+
+    let Ok(entries) = fs::read_dir(dir) else {
+        return (l1, l2);
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !entry.file_name().to_string_lossy().starts_with("index") {
+            continue;
+        }
+
+        let read = |name: &str| fs::read_to_string(path.join(name)).ok();
+        let (Some(level), Some(kind), Some(size)) = (read("level"), read("type"), read("size")) else {
+            continue;
+        };
+        if !matches!(kind.trim(), "Data" | "Unified") {
+            continue;
+        }
+
+        let size = parse_cache_size(&size);
+        match level.trim() {
+            "1" => l1 = l1.or(size),
+            "2" => l2 = l2.or(size),
+            _ => {}
+        }
+    }
+
+    (l1, l2)
 }
-const fn get_buffered_state_layout<const BUFFER_SIZE: usize>() -> Result<Layout, LayoutError> {
-    Layout::from_size_align(size_of::<BufferedState<BUFFER_SIZE>>(), BUFFERED_STATE_ALIGNMENT)
+
+/// Parses cache sizes as formatted by sysfs, i.e. `48K`, `2048K` or `1M`.
+fn parse_cache_size(size: &str) -> Option<usize> {
+    let size = size.trim();
+    let (digits, multiplier) = match size.as_bytes().last()? {
+        b'K' => (&size[..size.len() - 1], 1024),
+        b'M' => (&size[..size.len() - 1], 1024 * 1024),
+        b'G' => (&size[..size.len() - 1], 1024 * 1024 * 1024),
+        _ => (size, 1),
+    };
+    digits.parse::<usize>().ok()?.checked_mul(multiplier)
 }
 
-impl<const BUFFER_SIZE: usize> Shishua<BUFFER_SIZE> {
-    pub const LAYOUT: Layout = get_buffered_state_layout_unchecked::<BUFFER_SIZE>();
+impl<const BUFFER_SIZE: usize, const STEPS_PER_DRAW: usize> ShishuaIncremental<BUFFER_SIZE, STEPS_PER_DRAW> {
+    const STEP_SIZE: usize = 128;
+    const STEPS: usize = BUFFER_SIZE / Self::STEP_SIZE;
 
-    #[inline(always)]
-    fn fill_bytes_arr<const N: usize>(&mut self, dest: &mut [u8; N]) {
-        unsafe {
-            let state = self.state.as_mut();
+    pub fn from_snapshot(snapshot: &ShishuaSnapshot<BUFFER_SIZE>) -> Self {
+        Self::new(Shishua::from_snapshot(snapshot))
+    }
 
-            state.ensure_buffered(N);
+    fn new(rng: Shishua<BUFFER_SIZE>) -> Self {
+        let next_buffer_state = unsafe { rng.state.as_ref().state };
+        Self {
+            rng,
+            next_buffer_state,
+            steps: 0,
+        }
+    }
 
-            let src = state
-                .buffer
-                .0
-                .as_slice()
-                .get_unchecked(state.buffer_index..state.buffer_index + N);
-            dest.copy_from_slice(src);
-            state.buffer_index += N;
-        };
+    /// Takes a compact snapshot of the generator which can later be restored
+    /// to produce the exact same continuation. Compatible with [`Shishua`] snapshots.
+    pub fn snapshot(&self) -> ShishuaSnapshot<BUFFER_SIZE> {
+        self.rng.snapshot()
+    }
+
+    /// Restores the generator to the point the snapshot was taken, regenerating the buffer.
+    pub fn restore(&mut self, snapshot: &ShishuaSnapshot<BUFFER_SIZE>) {
+        *self = Self::new(Shishua::from_snapshot(snapshot));
     }
 
     #[inline(always)]
     pub fn buffer_index(&self) -> usize {
-        let state = unsafe { self.state.as_ref() };
-        state.buffer_index
+        self.rng.buffer_index()
     }
 
     #[inline(always)]
@@ -82,142 +907,560 @@ impl<const BUFFER_SIZE: usize> Shishua<BUFFER_SIZE> {
         let v = self.next_u64();
         (v >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
     }
+
+    #[inline(always)]
+    fn ensure_buffered(&mut self, size: usize) {
+        if BUFFER_SIZE - self.buffer_index() < size {
+            self.next_buffer();
+        }
+    }
+
+    /// Generates up to `STEPS_PER_DRAW` steps of the next buffer into the consumed part of the current one.
+    #[inline(always)]
+    fn step(&mut self) {
+        let consumed_steps = self.buffer_index() / Self::STEP_SIZE;
+        if self.steps < consumed_steps {
+            self.generate_steps(consumed_steps.min(self.steps + STEPS_PER_DRAW));
+        }
+    }
+
+    fn generate_steps(&mut self, until: usize) {
+        let state = unsafe { self.rng.state.as_mut() };
+        let range = self.steps * Self::STEP_SIZE..until * Self::STEP_SIZE;
+        unsafe {
+            state.state.prng_gen(&mut state.buffer.0[range]);
+        }
+        self.steps = until;
+    }
+
+    /// Generates the steps that are left of the next buffer and switches to it.
+    #[cold]
+    #[inline(never)]
+    fn next_buffer(&mut self) {
+        self.generate_steps(Self::STEPS);
+
+        let state = unsafe { self.rng.state.as_mut() };
+        state.buffer_state = self.next_buffer_state;
+        state.buffer_index = 0;
+        self.next_buffer_state = state.state;
+        self.steps = 0;
+    }
+
+    #[cold]
+    #[inline(never)]
+    fn fill_bytes_chunked(&mut self, dest: &mut [u8]) {
+        // Can't serve more than a buffer at a time
+        for chunk in dest.chunks_mut(BUFFER_SIZE) {
+            self.fill_bytes(chunk);
+        }
+    }
 }
 
-impl<const BUFFER_SIZE: usize> SimdRand for Shishua<BUFFER_SIZE> {
+impl<const BUFFER_SIZE: usize, const STEPS_PER_DRAW: usize> SimdRand
+    for ShishuaIncremental<BUFFER_SIZE, STEPS_PER_DRAW>
+{
     #[inline(always)]
     fn next_m256i(&mut self) -> __m256i {
-        const SIZE: usize = mem::size_of::<__m256i>();
-        unsafe {
-            let state = self.state.as_mut();
+        self.ensure_buffered(mem::size_of::<__m256i>());
+        let vector = self.rng.next_m256i();
+        self.step();
+        vector
+    }
+}
+
+impl<const BUFFER_SIZE: usize, const STEPS_PER_DRAW: usize> SeedableRng
+    for ShishuaIncremental<BUFFER_SIZE, STEPS_PER_DRAW>
+{
+    type Seed = [u8; 32];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        Self::new(Shishua::from_seed(seed))
+    }
+}
 
-            state.ensure_buffered(SIZE);
+impl<const BUFFER_SIZE: usize, const STEPS_PER_DRAW: usize> RngCore
+    for ShishuaIncremental<BUFFER_SIZE, STEPS_PER_DRAW>
+{
+    #[inline(always)]
+    fn next_u32(&mut self) -> u32 {
+        self.ensure_buffered(mem::size_of::<u32>());
+        let value = self.rng.next_u32();
+        self.step();
+        value
+    }
 
-            let src = &state.buffer.0[state.buffer_index];
+    #[inline(always)]
+    fn next_u64(&mut self) -> u64 {
+        self.ensure_buffered(mem::size_of::<u64>());
+        let value = self.rng.next_u64();
+        self.step();
+        value
+    }
 
-            let vector = _mm256_load_si256(transmute::<_, *const __m256i>(src));
+    #[inline(always)]
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        if dest.len() > BUFFER_SIZE {
+            return self.fill_bytes_chunked(dest);
+        }
 
-            state.buffer_index += SIZE;
+        self.ensure_buffered(dest.len());
+        self.rng.fill_bytes(dest);
+        self.step();
+    }
 
-            vector
+    #[inline(always)]
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// Size in bytes of a serialized [`ShishuaSnapshot`].
+pub const SHISHUA_SNAPSHOT_SIZE: usize = mem::size_of::<RawState>() + 2 * mem::size_of::<u64>();
+
+/// Compact snapshot of a [`Shishua`] or [`ShishuaInline`] generator.
+///
+/// Only the raw generator state and the position in the buffer is stored,
+/// the buffer itself is regenerated when restoring.
+/// A restored generator produces exactly the same continuation as the one the snapshot was taken from.
+/// Snapshots can be serialized to and from bytes using [`ShishuaSnapshot::to_bytes`] and [`ShishuaSnapshot::from_bytes`].
+/// The byte format is the raw state as little endian 64bit words followed by the buffer index and `BUFFER_SIZE`.
+#[derive(Clone, Copy, Debug)]
+pub struct ShishuaSnapshot<const BUFFER_SIZE: usize = DEFAULT_BUFFER_SIZE> {
+    state: RawState,
+    buffer_index: usize,
+}
+
+impl<const BUFFER_SIZE: usize> ShishuaSnapshot<BUFFER_SIZE> {
+    pub fn to_bytes(&self) -> [u8; SHISHUA_SNAPSHOT_SIZE] {
+        const STATE_SIZE: usize = mem::size_of::<RawState>();
+
+        let mut bytes = [0u8; SHISHUA_SNAPSHOT_SIZE];
+        // RawState is repr(C) and only consists of vectors, so the raw bytes
+        // are the little endian u64 lanes in order
+        let state = unsafe { mem::transmute::<RawState, [u8; STATE_SIZE]>(self.state) };
+        bytes[..STATE_SIZE].copy_from_slice(&state);
+        bytes[STATE_SIZE..STATE_SIZE + 8].copy_from_slice(&(self.buffer_index as u64).to_le_bytes());
+        bytes[STATE_SIZE + 8..].copy_from_slice(&(BUFFER_SIZE as u64).to_le_bytes());
+        bytes
+    }
+
+    /// Deserializes a snapshot produced by [`ShishuaSnapshot::to_bytes`].
+    /// Fails if the snapshot was taken from a generator with a different `BUFFER_SIZE`
+    /// or if the buffer index is out of range.
+    pub fn from_bytes(bytes: &[u8; SHISHUA_SNAPSHOT_SIZE]) -> Result<Self, ShishuaError> {
+        const STATE_SIZE: usize = mem::size_of::<RawState>();
+
+        let buffer_index = u64::from_le_bytes(bytes[STATE_SIZE..STATE_SIZE + 8].try_into().unwrap());
+        let buffer_size = u64::from_le_bytes(bytes[STATE_SIZE + 8..].try_into().unwrap());
+        if buffer_size != BUFFER_SIZE as u64 || buffer_index > buffer_size {
+            return Err(ShishuaError::InvalidSnapshot);
         }
+
+        let state: [u8; STATE_SIZE] = bytes[..STATE_SIZE].try_into().unwrap();
+        let state = unsafe { mem::transmute::<[u8; STATE_SIZE], RawState>(state) };
+
+        Ok(Self {
+            state,
+            buffer_index: buffer_index as usize,
+        })
     }
 }
 
-impl<const BUFFER_SIZE: usize> SeedableRng for Shishua<BUFFER_SIZE> {
+/// Size in bytes of the [`ShishuaResults`] block generated by [`ShishuaCore`] per call.
+pub const SHISHUA_CORE_BLOCK_SIZE: usize = 128 * 4;
+
+/// The unbuffered Shishua engine, generating 128 bytes per step.
+/// Implements [`BlockRngCore`] so that `BlockRng<ShishuaCore>` (or `BlockRng64<ShishuaCore<u64>>`)
+/// can be used as a regular [`RngCore`], or it can generate straight into your own memory
+/// through [`ShishuaCore::generate_into`].
+///
+/// Produces the same byte stream as [`Shishua`] for the same seed,
+/// as long as output is drawn in whole blocks.
+#[derive(Clone, Debug)]
+pub struct ShishuaCore<T = u32> {
+    state: RawState,
+    _item: PhantomData<T>,
+}
+
+/// The block of results generated by [`ShishuaCore`], aligned for vector stores.
+/// Can be viewed as `u32`'s or `u64`'s depending on the `Item` of the core.
+#[derive(Clone, Copy)]
+#[repr(C, align(32))]
+pub struct ShishuaResults<T = u32>([u8; SHISHUA_CORE_BLOCK_SIZE], PhantomData<T>);
+
+impl<T> ShishuaCore<T> {
+    /// Generates straight into `dest`. Output is produced in 128 byte steps,
+    /// if the length of `dest` is not a multiple of 128 the rest of the last step is discarded.
+    pub fn generate_into(&mut self, dest: &mut [u8]) {
+        let len = dest.len() - dest.len() % 128;
+        let (body, tail) = dest.split_at_mut(len);
+        unsafe {
+            self.state.prng_gen(body);
+        }
+
+        if !tail.is_empty() {
+            let mut step = [0u8; 128];
+            unsafe {
+                self.state.prng_gen(&mut step);
+            }
+            tail.copy_from_slice(&step[..tail.len()]);
+        }
+    }
+}
+
+impl<T> BlockRngCore for ShishuaCore<T>
+where
+    ShishuaResults<T>: AsRef<[T]> + AsMut<[T]>,
+{
+    type Item = T;
+    type Results = ShishuaResults<T>;
+
+    #[inline(always)]
+    fn generate(&mut self, results: &mut Self::Results) {
+        unsafe {
+            self.state.prng_gen(&mut results.0);
+        }
+    }
+}
+
+impl<T> SeedableRng for ShishuaCore<T> {
     type Seed = [u8; 32];
 
     fn from_seed(seed: Self::Seed) -> Self {
-        assert!(
-            get_buffered_state_layout::<BUFFER_SIZE>().is_ok(),
-            "couldnt construct memory layout of Shishua buffered state"
-        );
-        assert!(
-            BUFFER_SIZE.is_power_of_two(),
-            "The Shishua buffer size must be a power of 2"
-        );
-        assert!(
-            BUFFER_SIZE >= 256,
-            "The Shishua buffer size must be >= 256 (and power of 2)"
-        );
+        let mut iseed = [0; 4];
+        read_u64_into(&seed[..], iseed.as_mut_slice());
 
-        let ptr = unsafe {
-            let ptr = alloc::alloc(Self::LAYOUT) as *mut BufferedState<BUFFER_SIZE>;
+        unsafe {
+            let mut state: RawState = mem::zeroed();
+            state.prng_init(&iseed);
+            Self {
+                state,
+                _item: PhantomData,
+            }
+        }
+    }
+}
 
-            let buffered_state = ptr.as_mut().expect("Failed to allocate state for Shishua");
+impl<T> ShishuaResults<T> {
+    pub fn as_bytes(&self) -> &[u8; SHISHUA_CORE_BLOCK_SIZE] {
+        &self.0
+    }
+}
 
-            let mut iseed = [0; 4];
-            read_u64_into(&seed[..], iseed.as_mut_slice());
+impl<T> Default for ShishuaResults<T> {
+    fn default() -> Self {
+        Self([0; SHISHUA_CORE_BLOCK_SIZE], PhantomData)
+    }
+}
 
-            buffered_state.state.prng_init(mem::transmute::<_, &[_; 4]>(&iseed));
-            buffered_state.rebuffer();
+impl AsRef<[u32]> for ShishuaResults<u32> {
+    fn as_ref(&self) -> &[u32] {
+        // The block is aligned to 32 bytes, so it's valid to view as u32's
+        unsafe {
+            slice::from_raw_parts(
+                self.0.as_ptr() as *const u32,
+                SHISHUA_CORE_BLOCK_SIZE / size_of::<u32>(),
+            )
+        }
+    }
+}
 
-            NonNull::new_unchecked(ptr)
-        };
+impl AsMut<[u32]> for ShishuaResults<u32> {
+    fn as_mut(&mut self) -> &mut [u32] {
+        unsafe {
+            slice::from_raw_parts_mut(
+                self.0.as_mut_ptr() as *mut u32,
+                SHISHUA_CORE_BLOCK_SIZE / size_of::<u32>(),
+            )
+        }
+    }
+}
 
-        Self { state: ptr }
+impl AsRef<[u64]> for ShishuaResults<u64> {
+    fn as_ref(&self) -> &[u64] {
+        // The block is aligned to 32 bytes, so it's valid to view as u64's
+        unsafe {
+            slice::from_raw_parts(
+                self.0.as_ptr() as *const u64,
+                SHISHUA_CORE_BLOCK_SIZE / size_of::<u64>(),
+            )
+        }
     }
 }
 
-impl<const BUFFER_SIZE: usize> Drop for Shishua<BUFFER_SIZE> {
-    fn drop(&mut self) {
-        let ptr = self.state.as_ptr();
+impl AsMut<[u64]> for ShishuaResults<u64> {
+    fn as_mut(&mut self) -> &mut [u64] {
         unsafe {
-            alloc::dealloc(ptr as *mut u8, Self::LAYOUT);
+            slice::from_raw_parts_mut(
+                self.0.as_mut_ptr() as *mut u64,
+                SHISHUA_CORE_BLOCK_SIZE / size_of::<u64>(),
+            )
         }
     }
 }
 
-impl<const BUFFER_SIZE: usize> RngCore for Shishua<BUFFER_SIZE> {
+// The buffer is either a `[u8; BUFFER_SIZE]` or an unsized `[u8]` for runtime sized buffers.
+// The latter is why the buffer has to be the last field, and why the layout is fixed with repr(C)
+#[derive(Clone)]
+#[repr(C)]
+struct BufferedState<T: ?Sized> {
+    state: RawState,
+    // The state that generated the current buffer, needed to regenerate it from a snapshot
+    buffer_state: RawState,
+    buffer_index: usize,
+    buffer: BufferedStateBuffer<T>,
+}
+
+#[derive(Clone)]
+#[repr(align(32))]
+pub(super) struct BufferedStateBuffer<T: ?Sized>(pub(super) T);
+
+impl<T: ?Sized + AsRef<[u8]> + AsMut<[u8]>> BufferedState<T> {
+    // For fixed size buffers this is constant after inlining
     #[inline(always)]
-    fn next_u32(&mut self) -> u32 {
-        let mut result: u32 = 0;
-        let mut bytes = unsafe { mem::transmute::<_, &mut [u8; 4]>(&mut result) };
-        self.fill_bytes_arr(&mut bytes);
-        result
+    fn buffer_size(&self) -> usize {
+        self.buffer.0.as_ref().len()
+    }
+
+    fn init(&mut self, seed: &[u8; 32]) {
+        let mut iseed = [0; 4];
+        read_u64_into(&seed[..], iseed.as_mut_slice());
+
+        unsafe {
+            self.state.prng_init(&iseed);
+        }
+        self.rebuffer();
     }
 
     #[inline(always)]
-    fn next_u64(&mut self) -> u64 {
-        let mut result: u64 = 0;
-        let mut bytes = unsafe { mem::transmute::<_, &mut [u8; 8]>(&mut result) };
-        self.fill_bytes_arr(&mut bytes);
-        result
+    fn fill_bytes_arr<const N: usize>(&mut self, dest: &mut [u8; N]) {
+        self.ensure_buffered(N);
+
+        unsafe {
+            let src = self
+                .buffer
+                .0
+                .as_ref()
+                .get_unchecked(self.buffer_index..self.buffer_index + N);
+            dest.copy_from_slice(src);
+        };
+        self.buffer_index += N;
     }
 
     #[inline(always)]
     fn fill_bytes(&mut self, dest: &mut [u8]) {
         let size = dest.len();
+        if size > self.buffer_size() {
+            return self.fill_bytes_chunked(dest);
+        }
 
-        unsafe {
-            let state = self.state.as_mut();
-
-            state.ensure_buffered(size);
+        self.ensure_buffered(size);
 
-            let src = state
+        unsafe {
+            let src = self
                 .buffer
                 .0
-                .as_slice()
-                .get_unchecked(state.buffer_index..state.buffer_index + size);
+                .as_ref()
+                .get_unchecked(self.buffer_index..self.buffer_index + size);
             dest.copy_from_slice(src);
-            state.buffer_index += size;
         };
+        self.buffer_index += size;
+    }
+
+    #[cold]
+    #[inline(never)]
+    fn fill_bytes_chunked(&mut self, dest: &mut [u8]) {
+        // Can't serve more than a buffer at a time
+        for chunk in dest.chunks_mut(self.buffer_size()) {
+            self.fill_bytes(chunk);
+        }
+    }
+
+    fn fill_u64(&mut self, dest: &mut [u64]) {
+        self.fill_copied(dest)
+    }
+
+    fn fill_u32(&mut self, dest: &mut [u32]) {
+        self.fill_copied(dest)
+    }
+
+    /// Copies whole elements straight from the buffer, `E` being a plain integer valid for any bits.
+    fn fill_copied<E: Copy>(&mut self, mut dest: &mut [E]) {
+        let size = mem::size_of::<E>();
+
+        while !dest.is_empty() {
+            self.ensure_buffered(size);
+
+            let n = ((self.buffer_size() - self.buffer_index) / size).min(dest.len());
+            let (head, tail) = dest.split_at_mut(n);
+            unsafe {
+                let src = self.buffer.0.as_ref().as_ptr().add(self.buffer_index);
+                std::ptr::copy_nonoverlapping(src, head.as_mut_ptr() as *mut u8, n * size);
+            }
+            self.buffer_index += n * size;
+            dest = tail;
+        }
+    }
+
+    fn fill_f64(&mut self, mut dest: &mut [f64]) {
+        const SIZE: usize = mem::size_of::<u64>();
+        const LANES: usize = 4;
+
+        while !dest.is_empty() {
+            self.ensure_buffered(SIZE);
+
+            let n = ((self.buffer_size() - self.buffer_index) / SIZE).min(dest.len());
+            let (head, tail) = dest.split_at_mut(n);
+            unsafe {
+                let src = self.buffer.0.as_ref().as_ptr().add(self.buffer_index);
+                let dst = head.as_mut_ptr();
+
+                let mut i = 0;
+                while i + LANES <= n {
+                    let v = _mm256_loadu_si256(src.add(i * SIZE) as *const __m256i);
+                    _mm256_storeu_pd(dst.add(i), m256i_to_unit_m256d(v));
+                    i += LANES;
+                }
+                while i < n {
+                    let v = (src.add(i * SIZE) as *const u64).read_unaligned();
+                    *dst.add(i) = (v >> 11) as f64 * (1.0 / (1u64 << 53) as f64);
+                    i += 1;
+                }
+            }
+            self.buffer_index += n * SIZE;
+            dest = tail;
+        }
+    }
+
+    fn fill_f32(&mut self, mut dest: &mut [f32]) {
+        const SIZE: usize = mem::size_of::<u32>();
+        const LANES: usize = 8;
+
+        while !dest.is_empty() {
+            self.ensure_buffered(SIZE);
+
+            let n = ((self.buffer_size() - self.buffer_index) / SIZE).min(dest.len());
+            let (head, tail) = dest.split_at_mut(n);
+            unsafe {
+                let src = self.buffer.0.as_ref().as_ptr().add(self.buffer_index);
+                let dst = head.as_mut_ptr();
+
+                let mut i = 0;
+                while i + LANES <= n {
+                    let v = _mm256_loadu_si256(src.add(i * SIZE) as *const __m256i);
+                    _mm256_storeu_ps(dst.add(i), m256i_to_unit_m256(v));
+                    i += LANES;
+                }
+                while i < n {
+                    let v = (src.add(i * SIZE) as *const u32).read_unaligned();
+                    *dst.add(i) = (v >> 8) as f32 * (1.0f32 / (1u32 << 24) as f32);
+                    i += 1;
+                }
+            }
+            self.buffer_index += n * SIZE;
+            dest = tail;
+        }
     }
 
     #[inline(always)]
-    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
-        self.fill_bytes(dest);
-        Ok(())
+    fn next_u32(&mut self) -> u32 {
+        let mut result: u32 = 0;
+        let bytes = unsafe { mem::transmute::<&mut u32, &mut [u8; 4]>(&mut result) };
+        self.fill_bytes_arr(bytes);
+        result
     }
-}
 
-struct BufferedState<const BUFFER_SIZE: usize> {
-    state: RawState,
-    buffer: BufferedStateBuffer<BUFFER_SIZE>,
-    buffer_index: usize,
-}
+    #[inline(always)]
+    fn next_u64(&mut self) -> u64 {
+        let mut result: u64 = 0;
+        let bytes = unsafe { mem::transmute::<&mut u64, &mut [u8; 8]>(&mut result) };
+        self.fill_bytes_arr(bytes);
+        result
+    }
 
-#[repr(align(32))]
-struct BufferedStateBuffer<const BUFFER_SIZE: usize>([u8; BUFFER_SIZE]);
+    #[inline(always)]
+    fn next_m256i(&mut self) -> __m256i {
+        const SIZE: usize = mem::size_of::<__m256i>();
+
+        self.ensure_buffered(SIZE);
+
+        unsafe {
+            let src = &self.buffer.0.as_ref()[self.buffer_index];
+
+            // The buffer itself is aligned, but the index is only guaranteed to be
+            // a multiple of 32 if all draws have been vectors. loadu is just as fast on aligned data
+            let vector = _mm256_loadu_si256(transmute::<_, *const __m256i>(src));
+
+            self.buffer_index += SIZE;
+
+            vector
+        }
+    }
 
-impl<const BUFFER_SIZE: usize> BufferedState<BUFFER_SIZE> {
     #[inline(always)] // This should be inlined, this branch will be checked every time we sample
     fn ensure_buffered(&mut self, size: usize) {
-        if BUFFER_SIZE - self.buffer_index < size {
+        if self.buffer_size() - self.buffer_index < size {
             self.rebuffer();
         }
     }
 
+    fn discard(&mut self, n_bytes: u64) {
+        let buffer_size = self.buffer_size();
+        let available = (buffer_size - self.buffer_index) as u64;
+        if n_bytes <= available {
+            self.buffer_index += n_bytes as usize;
+            return;
+        }
+
+        // The rest of the current buffer is consumed, then whole buffers are skipped
+        // without materializing them before landing somewhere in a fresh buffer.
+        // Landing exactly on a buffer boundary leaves the last skipped buffer fully consumed,
+        // same as reading would, so that the next rebuffer happens lazily
+        let remaining = n_bytes - available;
+        let (buffers, offset) = match (
+            remaining / buffer_size as u64,
+            (remaining % buffer_size as u64) as usize,
+        ) {
+            (buffers, 0) => (buffers - 1, buffer_size),
+            (buffers, offset) => (buffers, offset),
+        };
+
+        unsafe {
+            self.state.prng_skip(buffers * (buffer_size / 128) as u64);
+        }
+        self.rebuffer();
+        self.buffer_index = offset;
+    }
+
     #[cold] // This attribute seems to make LLVM organize jumps/branches better
     #[inline(never)] // This should not be inlined, as entering the branch above is the rare case
     fn rebuffer(&mut self) {
+        self.buffer_state = self.state;
         unsafe {
-            self.state.prng_gen(&mut self.buffer.0[..]);
+            self.state.prng_gen(self.buffer.0.as_mut());
         }
         self.buffer_index = 0;
     }
 }
 
+impl<const BUFFER_SIZE: usize> BufferedState<[u8; BUFFER_SIZE]> {
+    fn snapshot(&self) -> ShishuaSnapshot<BUFFER_SIZE> {
+        ShishuaSnapshot {
+            state: self.buffer_state,
+            buffer_index: self.buffer_index,
+        }
+    }
+
+    fn restore(&mut self, snapshot: &ShishuaSnapshot<BUFFER_SIZE>) {
+        self.state = snapshot.state;
+        self.rebuffer();
+        self.buffer_index = snapshot.buffer_index;
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
 struct RawState {
     state: [__m256i; 4],
     output: [__m256i; 4],
@@ -265,29 +1508,27 @@ impl RawState {
         }
     }
 
-    unsafe fn prng_gen(&mut self, buf: &mut [u8]) {
-        let mut o0 = self.output[0];
-        let mut o1 = self.output[1];
-        let mut o2 = self.output[2];
-        let mut o3 = self.output[3];
-        let mut s0 = self.state[0];
-        let mut s1 = self.state[1];
-        let mut s2 = self.state[2];
-        let mut s3 = self.state[3];
-        let mut t0: __m256i;
-        let mut t1: __m256i;
-        let mut t2: __m256i;
-        let mut t3: __m256i;
-        let mut u0: __m256i;
-        let mut u1: __m256i;
-        let mut u2: __m256i;
-        let mut u3: __m256i;
+    /// Advances the state by `steps` 128-byte blocks, same as `prng_gen` but without storing any output.
+    unsafe fn prng_skip(&mut self, steps: u64) {
+        let mut state = self.state;
         let mut counter = self.counter;
+        let mut output = self.output;
 
-        let shu0 = _mm256_set_epi32(4, 3, 2, 1, 0, 7, 6, 5);
-        let shu1 = _mm256_set_epi32(2, 1, 0, 7, 6, 5, 4, 3);
+        // The outputs only depend on the state of the current step,
+        // the compiler drops the ones that get overwritten by the next step
+        for _ in 0..steps {
+            output = Self::prng_round(&mut state, &mut counter);
+        }
 
-        let increment = _mm256_set_epi64x(1, 3, 5, 7);
+        self.output = output;
+        self.state = state;
+        self.counter = counter;
+    }
+
+    unsafe fn prng_gen(&mut self, buf: &mut [u8]) {
+        let mut state = self.state;
+        let mut counter = self.counter;
+        let [mut o0, mut o1, mut o2, mut o3] = self.output;
 
         assert!(buf.len() % 128 == 0);
 
@@ -298,40 +1539,51 @@ impl RawState {
             _mm256_storeu_si256(buf_ptr.add(i + 64) as *mut __m256i, o2);
             _mm256_storeu_si256(buf_ptr.add(i + 96) as *mut __m256i, o3);
 
-            s1 = _mm256_add_epi64(s1, counter);
-            s3 = _mm256_add_epi64(s3, counter);
-            counter = _mm256_add_epi64(counter, increment);
-
-            u0 = _mm256_srli_epi64::<1>(s0);
-            u1 = _mm256_srli_epi64::<3>(s1);
-            u2 = _mm256_srli_epi64::<1>(s2);
-            u3 = _mm256_srli_epi64::<3>(s3);
-            t0 = _mm256_permutevar8x32_epi32(s0, shu0);
-            t1 = _mm256_permutevar8x32_epi32(s1, shu1);
-            t2 = _mm256_permutevar8x32_epi32(s2, shu0);
-            t3 = _mm256_permutevar8x32_epi32(s3, shu1);
-
-            s0 = _mm256_add_epi64(t0, u0);
-            s1 = _mm256_add_epi64(t1, u1);
-            s2 = _mm256_add_epi64(t2, u2);
-            s3 = _mm256_add_epi64(t3, u3);
-
-            // Two orthogonally grown pieces evolving independently, XORed.
-            o0 = _mm256_xor_si256(u0, t1);
-            o1 = _mm256_xor_si256(u2, t3);
-            o2 = _mm256_xor_si256(s0, s3);
-            o3 = _mm256_xor_si256(s2, s1);
-        }
-
-        self.output[0] = o0;
-        self.output[1] = o1;
-        self.output[2] = o2;
-        self.output[3] = o3;
-        self.state[0] = s0;
-        self.state[1] = s1;
-        self.state[2] = s2;
-        self.state[3] = s3;
-        self.counter = counter;
+            [o0, o1, o2, o3] = Self::prng_round(&mut state, &mut counter);
+        }
+
+        self.output = [o0, o1, o2, o3];
+        self.state = state;
+        self.counter = counter;
+    }
+
+    /// One round of the generator, advancing `state` and `counter` and returning the output for the next 128 bytes.
+    /// Shared by `prng_gen` and `prng_skip`, so that skipping always matches generating.
+    #[inline(always)]
+    unsafe fn prng_round(state: &mut [__m256i; 4], counter: &mut __m256i) -> [__m256i; 4] {
+        let shu0 = _mm256_set_epi32(4, 3, 2, 1, 0, 7, 6, 5);
+        let shu1 = _mm256_set_epi32(2, 1, 0, 7, 6, 5, 4, 3);
+
+        let increment = _mm256_set_epi64x(1, 3, 5, 7);
+
+        let [s0, mut s1, s2, mut s3] = *state;
+
+        s1 = _mm256_add_epi64(s1, *counter);
+        s3 = _mm256_add_epi64(s3, *counter);
+        *counter = _mm256_add_epi64(*counter, increment);
+
+        let u0 = _mm256_srli_epi64::<1>(s0);
+        let u1 = _mm256_srli_epi64::<3>(s1);
+        let u2 = _mm256_srli_epi64::<1>(s2);
+        let u3 = _mm256_srli_epi64::<3>(s3);
+        let t0 = _mm256_permutevar8x32_epi32(s0, shu0);
+        let t1 = _mm256_permutevar8x32_epi32(s1, shu1);
+        let t2 = _mm256_permutevar8x32_epi32(s2, shu0);
+        let t3 = _mm256_permutevar8x32_epi32(s3, shu1);
+
+        let s0 = _mm256_add_epi64(t0, u0);
+        let s1 = _mm256_add_epi64(t1, u1);
+        let s2 = _mm256_add_epi64(t2, u2);
+        let s3 = _mm256_add_epi64(t3, u3);
+        *state = [s0, s1, s2, s3];
+
+        // Two orthogonally grown pieces evolving independently, XORed.
+        [
+            _mm256_xor_si256(u0, t1),
+            _mm256_xor_si256(u2, t3),
+            _mm256_xor_si256(s0, s3),
+            _mm256_xor_si256(s2, s1),
+        ]
     }
 }
 
@@ -345,8 +1597,11 @@ const PHI: [u64; 16] = [
 
 #[cfg(test)]
 mod tests {
+    use std::cell::Cell;
+
     use itertools::Itertools;
     use rand::Rng;
+    use rand_core::block::{BlockRng, BlockRng64};
     use serial_test::parallel;
 
     type RngImpl = super::Shishua<DEFAULT_BUFFER_SIZE>;
@@ -359,7 +1614,7 @@ mod tests {
     #[test]
     #[parallel]
     fn alignment() {
-        assert!(mem::align_of::<BufferedState<DEFAULT_BUFFER_SIZE>>() % 32 == 0);
+        assert!(mem::align_of::<BufferedState<[u8; DEFAULT_BUFFER_SIZE]>>() % 32 == 0);
 
         let rng = create_with_zero_seed();
         let state = unsafe { rng.state.as_ref() };
@@ -429,6 +1684,558 @@ mod tests {
         assert!(rng.buffer_index() == 0);
     }
 
+    #[test]
+    #[parallel]
+    fn construction_in_global() {
+        let seed = get_predefined_seed();
+        let mut rng = super::Shishua::<DEFAULT_BUFFER_SIZE, Global>::try_from_seed_in(*seed, Global).unwrap();
+        let mut expected = create_with_predefined_seed();
+        assert!(rng.buffer_index() == 0);
+
+        for _ in 0..(DEFAULT_BUFFER_SIZE / 8) * 2 {
+            assert_eq!(expected.next_u64(), rng.next_u64());
+        }
+    }
+
+    #[test]
+    #[parallel]
+    fn construction_in_invalid_size() {
+        let seed = get_predefined_seed();
+        let result = super::Shishua::<127, Global>::try_from_seed_in(*seed, Global);
+        assert_eq!(result.err(), Some(ShishuaError::BufferSizeNotPowerOfTwo(127)));
+
+        let result = super::Shishua::<128, Global>::try_from_seed_in(*seed, Global);
+        assert_eq!(result.err(), Some(ShishuaError::BufferSizeTooSmall(128)));
+    }
+
+    #[test]
+    #[parallel]
+    fn construction_in_failing_allocator() {
+        struct FailingAllocator;
+
+        unsafe impl Allocator for FailingAllocator {
+            fn allocate(&self, _: Layout) -> Result<NonNull<[u8]>, AllocError> {
+                Err(AllocError)
+            }
+
+            unsafe fn deallocate(&self, _: NonNull<u8>, _: Layout) {
+                unreachable!()
+            }
+        }
+
+        let seed = get_predefined_seed();
+        let result = super::Shishua::<DEFAULT_BUFFER_SIZE, _>::try_from_seed_in(*seed, FailingAllocator);
+        assert_eq!(result.err(), Some(ShishuaError::Alloc(AllocError)));
+    }
+
+    #[test]
+    #[parallel]
+    fn construction_in_custom_allocator() {
+        #[derive(Default)]
+        struct CountingAllocator {
+            allocated: Cell<usize>,
+            deallocated: Cell<usize>,
+        }
+
+        unsafe impl Allocator for CountingAllocator {
+            fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+                assert_eq!(layout.align(), BUFFERED_STATE_ALIGNMENT);
+                self.allocated.set(self.allocated.get() + layout.size());
+                Global.allocate(layout)
+            }
+
+            unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+                self.deallocated.set(self.deallocated.get() + layout.size());
+                Global.deallocate(ptr, layout)
+            }
+        }
+
+        let allocator = CountingAllocator::default();
+        {
+            let seed = get_predefined_seed();
+            let mut rng = super::Shishua::<DEFAULT_BUFFER_SIZE, _>::try_from_seed_in(*seed, &allocator).unwrap();
+            let mut expected = create_with_predefined_seed();
+            assert_eq!(expected.next_u64(), rng.next_u64());
+
+            assert_eq!(allocator.allocated.get(), RngImpl::LAYOUT.size());
+            assert_eq!(allocator.deallocated.get(), 0);
+        }
+        assert_eq!(allocator.deallocated.get(), RngImpl::LAYOUT.size());
+    }
+
+    #[test]
+    #[parallel]
+    fn clone_same_stream() {
+        let mut rng = super::Shishua::<256>::from_seed(*get_predefined_seed());
+        for _ in 0..13 {
+            rng.next_u32();
+        }
+
+        let mut clone = rng.clone();
+        assert_eq!(rng.buffer_index(), clone.buffer_index());
+        for _ in 0..256 {
+            assert_eq!(rng.next_u64(), clone.next_u64());
+        }
+
+        let mut inline = ShishuaInline::<256>::from_seed(*get_predefined_seed());
+        inline.next_u32();
+        let mut clone = inline.clone();
+        for _ in 0..256 {
+            assert_eq!(inline.next_u64(), clone.next_u64());
+        }
+    }
+
+    #[test]
+    #[parallel]
+    fn snapshot_restore() {
+        let mut rng = super::Shishua::<256>::from_seed(*get_predefined_seed());
+        // Make sure we have gone through a couple of rebuffers and are in the middle of a buffer
+        for _ in 0..100 {
+            rng.next_u32();
+        }
+
+        let snapshot = rng.snapshot();
+        let expected: Vec<u64> = (0..256).map(|_| rng.next_u64()).collect();
+
+        let mut restored = super::Shishua::<256>::from_snapshot(&snapshot);
+        assert_eq!(restored.buffer_index(), snapshot.buffer_index);
+        let actual: Vec<u64> = (0..256).map(|_| restored.next_u64()).collect();
+        assert_eq!(expected, actual);
+
+        // Restoring an existing generator also rewinds it
+        rng.restore(&snapshot);
+        let actual: Vec<u64> = (0..256).map(|_| rng.next_u64()).collect();
+        assert_eq!(expected, actual);
+
+        let mut inline = ShishuaInline::<256>::from_snapshot(&snapshot);
+        let actual: Vec<u64> = (0..256).map(|_| inline.next_u64()).collect();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    #[parallel]
+    fn snapshot_bytes_roundtrip() {
+        let mut rng = super::Shishua::<256>::from_seed(*get_predefined_seed());
+        rng.next_u64();
+        rng.next_u32();
+
+        let bytes = rng.snapshot().to_bytes();
+        let snapshot = ShishuaSnapshot::<256>::from_bytes(&bytes).unwrap();
+        assert_eq!(bytes, snapshot.to_bytes());
+
+        let mut restored = super::Shishua::<256>::from_snapshot(&snapshot);
+        for _ in 0..256 {
+            assert_eq!(rng.next_u64(), restored.next_u64());
+        }
+
+        let result = ShishuaSnapshot::<512>::from_bytes(&bytes);
+        assert_eq!(result.err(), Some(ShishuaError::InvalidSnapshot));
+    }
+
+    #[test]
+    #[parallel]
+    fn send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+
+        assert_send_sync::<RngImpl>();
+        assert_send_sync::<ShishuaInline<DEFAULT_BUFFER_SIZE>>();
+    }
+
+    #[test]
+    #[parallel]
+    fn move_across_threads() {
+        let mut expected = create_with_predefined_seed();
+        let expected: Vec<u64> = (0..(DEFAULT_BUFFER_SIZE / 8) * 2)
+            .map(|_| expected.next_u64())
+            .collect();
+
+        let mut rngs: Vec<RngImpl> = (0..4).map(|_| create_with_predefined_seed()).collect();
+
+        // Seeded on this thread, sampled (and rebuffered) on the worker threads
+        let n = expected.len();
+        let results: Vec<Vec<u64>> = std::thread::scope(|s| {
+            let handles: Vec<_> = rngs
+                .iter_mut()
+                .map(|rng| s.spawn(move || (0..n).map(|_| rng.next_u64()).collect()))
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        for result in results {
+            assert_eq!(expected, result);
+        }
+
+        // Moving ownership into a thread, and getting it back
+        let rng = create_with_predefined_seed();
+        let mut rng = std::thread::scope(|s| {
+            s.spawn(move || {
+                let mut rng = rng;
+                rng.next_u64();
+                rng
+            })
+            .join()
+            .unwrap()
+        });
+        assert_eq!(expected[1], rng.next_u64());
+
+        // Shared references can be used from multiple threads
+        let rng = create_with_predefined_seed();
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| assert_eq!(rng.buffer_index(), 0));
+            }
+        });
+    }
+
+    #[test]
+    #[parallel]
+    fn inline_alignment() {
+        assert!(mem::align_of::<ShishuaInline<DEFAULT_BUFFER_SIZE>>() == BUFFERED_STATE_ALIGNMENT);
+
+        let rng = ShishuaInline::<DEFAULT_BUFFER_SIZE>::from_seed(*get_zero_seed());
+        let buf_alignment = mem::align_of_val(&rng.state.buffer);
+        assert!(buf_alignment % 32 == 0);
+        assert!((&rng.state.buffer as *const _ as usize) % 32 == 0);
+    }
+
+    #[test]
+    #[parallel]
+    fn inline_same_stream() {
+        let seed = get_predefined_seed();
+        let mut heap = super::Shishua::<256>::from_seed(*seed);
+        let mut inline = ShishuaInline::<256>::from_seed(*seed);
+
+        // Mix the different ways of sampling so that we go through several rebuffers,
+        // including the ones discarding leftover bytes
+        for _ in 0..64 {
+            assert_eq!(heap.next_u32(), inline.next_u32());
+            assert_eq!(heap.next_u64(), inline.next_u64());
+            assert_eq!(*heap.next_u64x4(), *inline.next_u64x4());
+            assert_eq!(heap.next_f32(), inline.next_f32());
+            assert_eq!(heap.next_f64(), inline.next_f64());
+
+            let mut heap_bytes = [0u8; 17];
+            let mut inline_bytes = [0u8; 17];
+            heap.fill_bytes(&mut heap_bytes);
+            inline.fill_bytes(&mut inline_bytes);
+            assert_eq!(heap_bytes, inline_bytes);
+            assert_eq!(heap.buffer_index(), inline.buffer_index());
+        }
+    }
+
+    #[test]
+    #[parallel]
+    #[should_panic]
+    fn inline_construction_invalid_size_power() {
+        let seed = get_predefined_seed();
+        let rng = ShishuaInline::<127>::from_seed(*seed);
+        assert!(rng.buffer_index() == 0);
+    }
+
+    #[test]
+    #[parallel]
+    fn discard_same_continuation() {
+        type Rng = super::Shishua<256>;
+        let mut base = Rng::from_seed(*get_predefined_seed());
+        base.next_u32();
+        base.next_u64();
+
+        for n in [0u64, 1, 7, 200, 244, 245, 256, 257, 500, 512, 1000, 256 * 17 + 3] {
+            let mut rng = base.clone();
+            let mut expected = base.clone();
+
+            rng.discard(n);
+            let mut byte = [0u8; 1];
+            for _ in 0..n {
+                expected.fill_bytes(&mut byte);
+            }
+
+            assert_eq!(rng.buffer_index(), expected.buffer_index(), "n = {n}");
+            for _ in 0..100 {
+                assert_eq!(rng.next_u64(), expected.next_u64(), "n = {n}");
+            }
+
+            // The snapshot after discarding should also be consistent
+            let mut restored = Rng::from_snapshot(&rng.snapshot());
+            assert_eq!(restored.next_u64(), rng.next_u64(), "n = {n}");
+        }
+    }
+
+    #[test]
+    #[parallel]
+    fn inline_discard_same_continuation() {
+        let mut rng = super::ShishuaInline::<256>::from_seed(*get_predefined_seed());
+        let mut expected = super::Shishua::<256>::from_seed(*get_predefined_seed());
+
+        let n = 256 * 5 + 100;
+        rng.discard(n);
+        let mut bytes = vec![0u8; n as usize];
+        for chunk in bytes.chunks_mut(256) {
+            expected.fill_bytes(chunk);
+        }
+
+        for _ in 0..100 {
+            assert_eq!(rng.next_u64(), expected.next_u64());
+        }
+    }
+
+    #[test]
+    #[parallel]
+    fn reseed_same_as_from_seed() {
+        let mut rng = create_with_zero_seed();
+        rng.next_u32();
+        rng.reseed(*get_predefined_seed());
+
+        let mut expected = create_with_predefined_seed();
+        assert_eq!(rng.buffer_index(), 0);
+        for _ in 0..(DEFAULT_BUFFER_SIZE / 8) * 2 {
+            assert_eq!(rng.next_u64(), expected.next_u64());
+        }
+
+        let mut rng = super::ShishuaInline::<256>::from_seed(*get_zero_seed());
+        rng.next_u64();
+        rng.reseed(*get_predefined_seed());
+
+        let mut expected = super::Shishua::<256>::from_seed(*get_predefined_seed());
+        for _ in 0..100 {
+            assert_eq!(rng.next_u64(), expected.next_u64());
+        }
+    }
+
+    #[test]
+    #[parallel]
+    fn core_results_alignment() {
+        assert_eq!(mem::align_of::<ShishuaResults<u32>>(), 32);
+        assert_eq!(mem::size_of::<ShishuaResults<u64>>(), SHISHUA_CORE_BLOCK_SIZE);
+    }
+
+    #[test]
+    #[parallel]
+    fn core_block_rng_same_stream() {
+        let mut rng = BlockRng::<ShishuaCore>::from_seed(*get_predefined_seed());
+        let mut expected = create_with_predefined_seed();
+
+        for _ in 0..(DEFAULT_BUFFER_SIZE / 4) * 2 {
+            assert_eq!(rng.next_u32(), expected.next_u32());
+        }
+    }
+
+    #[test]
+    #[parallel]
+    fn core_block_rng64_same_stream() {
+        let mut rng = BlockRng64::<ShishuaCore<u64>>::from_seed(*get_predefined_seed());
+        let mut expected = create_with_predefined_seed();
+
+        for _ in 0..(DEFAULT_BUFFER_SIZE / 8) * 2 {
+            assert_eq!(rng.next_u64(), expected.next_u64());
+        }
+    }
+
+    #[test]
+    #[parallel]
+    fn core_generate_into() {
+        let mut core = ShishuaCore::<u32>::from_seed(*get_predefined_seed());
+        let mut expected = super::Shishua::<256>::from_seed(*get_predefined_seed());
+
+        let mut bytes = [0u8; 256 * 3];
+        core.generate_into(&mut bytes);
+        let mut results = ShishuaResults::default();
+        core.generate(&mut results);
+
+        let mut expected_bytes = [0u8; 256 * 3 + SHISHUA_CORE_BLOCK_SIZE];
+        for chunk in expected_bytes.chunks_mut(256) {
+            expected.fill_bytes(chunk);
+        }
+        assert_eq!(&bytes[..], &expected_bytes[..256 * 3]);
+        assert_eq!(&results.as_bytes()[..], &expected_bytes[256 * 3..]);
+
+        // A partial step discards the rest of it
+        let mut core = ShishuaCore::<u32>::from_seed(*get_predefined_seed());
+        let mut bytes = [0u8; 200];
+        core.generate_into(&mut bytes);
+        assert_eq!(&bytes[..], &expected_bytes[..200]);
+        let mut bytes = [0u8; 128];
+        core.generate_into(&mut bytes);
+        assert_eq!(&bytes[..], &expected_bytes[256..384]);
+    }
+
+    #[test]
+    #[parallel]
+    fn dyn_layout_same_as_fixed() {
+        assert_eq!(
+            get_dyn_buffered_state_layout(256).unwrap(),
+            super::Shishua::<256>::LAYOUT
+        );
+        assert_eq!(
+            get_dyn_buffered_state_layout(DEFAULT_BUFFER_SIZE).unwrap(),
+            RngImpl::LAYOUT
+        );
+
+        let rng = ShishuaDyn::from_seed_with_buffer_size(*get_zero_seed(), 512);
+        let state = unsafe { rng.state.as_ref() };
+        assert_eq!(rng.buffer_size(), 512);
+        assert_eq!(mem::size_of_val(state), mem::size_of::<BufferedState<[u8; 512]>>());
+        assert!(state.buffer.0.as_ptr() as usize % 32 == 0);
+    }
+
+    #[test]
+    #[parallel]
+    fn dyn_same_stream() {
+        let mut rng = ShishuaDyn::from_seed_with_buffer_size(*get_predefined_seed(), 256);
+        let mut expected = super::Shishua::<256>::from_seed(*get_predefined_seed());
+
+        for _ in 0..100 {
+            assert_eq!(rng.next_u32(), expected.next_u32());
+            assert_eq!(rng.next_u64(), expected.next_u64());
+            let (v, e) = (rng.next_m256i(), expected.next_m256i());
+            assert_eq!(unsafe { mem::transmute::<_, [u64; 4]>(v) }, unsafe {
+                mem::transmute::<_, [u64; 4]>(e)
+            });
+            assert_eq!(rng.buffer_index(), expected.buffer_index());
+        }
+
+        let mut bytes = [0u8; 1000];
+        let mut expected_bytes = [0u8; 1000];
+        rng.fill_bytes(&mut bytes);
+        expected.fill_bytes(&mut expected_bytes);
+        assert_eq!(bytes, expected_bytes);
+
+        let mut rng = rng.clone();
+        for _ in 0..100 {
+            assert_eq!(rng.next_u64(), expected.next_u64());
+        }
+    }
+
+    #[test]
+    #[parallel]
+    fn dyn_construction_invalid_size() {
+        let result = ShishuaDyn::try_from_seed_in(*get_zero_seed(), 1000, Global);
+        assert_eq!(result.err(), Some(ShishuaError::BufferSizeNotPowerOfTwo(1000)));
+        let result = ShishuaDyn::try_from_seed_in(*get_zero_seed(), 128, Global);
+        assert_eq!(result.err(), Some(ShishuaError::BufferSizeTooSmall(128)));
+    }
+
+    #[test]
+    #[parallel]
+    fn dyn_auto() {
+        let rng = ShishuaDyn::auto(*get_zero_seed());
+        assert!(rng.buffer_size().is_power_of_two());
+        assert!(rng.buffer_size() >= 256);
+
+        let mut rng = Shishua::auto(*get_predefined_seed());
+        let mut expected = ShishuaDyn::from_seed_with_buffer_size(*get_predefined_seed(), auto_buffer_size());
+        for _ in 0..100 {
+            assert_eq!(rng.next_u64(), expected.next_u64());
+        }
+    }
+
+    #[test]
+    #[parallel]
+    fn dyn_from_seed_default_size() {
+        let mut rng = ShishuaDyn::from_seed(*get_predefined_seed());
+        let mut expected = RngImpl::from_seed(*get_predefined_seed());
+        assert_eq!(rng.buffer_size(), DEFAULT_BUFFER_SIZE);
+        for _ in 0..100 {
+            assert_eq!(rng.next_u64(), expected.next_u64());
+        }
+    }
+
+    #[test]
+    #[parallel]
+    fn cache_sizes() {
+        assert_eq!(parse_cache_size("48K\n"), Some(48 * 1024));
+        assert_eq!(parse_cache_size("2M"), Some(2 * 1024 * 1024));
+        assert_eq!(parse_cache_size("512"), Some(512));
+        assert_eq!(parse_cache_size("K"), None);
+        assert_eq!(parse_cache_size(""), None);
+
+        assert_eq!(buffer_size_for_caches(Some(48 * 1024), Some(2048 * 1024)), 32 * 1024);
+        assert_eq!(buffer_size_for_caches(Some(64 * 1024), None), 64 * 1024);
+        assert_eq!(buffer_size_for_caches(None, Some(1024 * 1024)), 64 * 1024);
+        assert_eq!(buffer_size_for_caches(Some(100), None), 256);
+        assert_eq!(buffer_size_for_caches(None, None), DEFAULT_BUFFER_SIZE);
+
+        let dir = std::env::temp_dir().join(format!("simd_rand_cache_sizes_{}", std::process::id()));
+        for (index, level, kind, size) in [
+            ("index0", "1", "Data", "48K"),
+            ("index1", "1", "Instruction", "32K"),
+            ("index2", "2", "Unified", "2048K"),
+        ] {
+            let path = dir.join(index);
+            fs::create_dir_all(&path).unwrap();
+            fs::write(path.join("level"), format!("{level}\n")).unwrap();
+            fs::write(path.join("type"), format!("{kind}\n")).unwrap();
+            fs::write(path.join("size"), format!("{size}\n")).unwrap();
+        }
+        let sizes = read_data_cache_sizes(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(sizes, (Some(48 * 1024), Some(2048 * 1024)));
+        assert_eq!(read_data_cache_sizes(Path::new("/does/not/exist")), (None, None));
+    }
+
+    #[test]
+    #[parallel]
+    fn incremental_same_stream() {
+        let mut rng = ShishuaIncremental::<256>::from_seed(*get_predefined_seed());
+        let mut expected = super::Shishua::<256>::from_seed(*get_predefined_seed());
+
+        for _ in 0..1000 {
+            assert_eq!(rng.next_u32(), expected.next_u32());
+            assert_eq!(rng.next_u64(), expected.next_u64());
+            let (v, e) = (rng.next_m256i(), expected.next_m256i());
+            assert_eq!(unsafe { mem::transmute::<_, [u64; 4]>(v) }, unsafe {
+                mem::transmute::<_, [u64; 4]>(e)
+            });
+            assert_eq!(rng.buffer_index(), expected.buffer_index());
+        }
+
+        for len in [1, 100, 200, 256, 300, 1000] {
+            let mut bytes = vec![0u8; len];
+            let mut expected_bytes = vec![0u8; len];
+            rng.fill_bytes(&mut bytes);
+            expected.fill_bytes(&mut expected_bytes);
+            assert_eq!(bytes, expected_bytes);
+            assert_eq!(rng.next_u64(), expected.next_u64());
+        }
+    }
+
+    #[test]
+    #[parallel]
+    fn incremental_keeps_up() {
+        let mut rng = ShishuaIncremental::<DEFAULT_BUFFER_SIZE>::from_seed(*get_predefined_seed());
+        let mut expected = create_with_predefined_seed();
+
+        for _ in 0..(DEFAULT_BUFFER_SIZE / 8) * 3 {
+            assert_eq!(rng.next_u64(), expected.next_u64());
+            // Never more than one step behind what has been consumed
+            assert!(rng.steps + 1 >= rng.buffer_index() / 128);
+        }
+
+        let mut rng = ShishuaIncremental::<256, 2>::from_seed(*get_predefined_seed());
+        let mut expected = super::Shishua::<256>::from_seed(*get_predefined_seed());
+        for _ in 0..1000 {
+            assert_eq!(rng.next_u32(), expected.next_u32());
+        }
+    }
+
+    #[test]
+    #[parallel]
+    fn incremental_snapshot_restore() {
+        let mut rng = ShishuaIncremental::<256>::from_seed(*get_predefined_seed());
+        for _ in 0..100 {
+            rng.next_u64();
+        }
+
+        let snapshot = rng.snapshot();
+        let mut restored = super::Shishua::<256>::from_snapshot(&snapshot);
+        let mut restored_incremental = ShishuaIncremental::<256>::from_snapshot(&snapshot);
+        for _ in 0..1000 {
+            let v = rng.next_u64();
+            assert_eq!(v, restored.next_u64());
+            assert_eq!(v, restored_incremental.next_u64());
+        }
+    }
+
     #[test]
     #[parallel]
     fn sample_u32() {
@@ -499,6 +2306,156 @@ mod tests {
         println!("{values:?}");
     }
 
+    #[test]
+    #[parallel]
+    fn sample_u32x8() {
+        let mut rng = create_with_predefined_seed();
+        rng.next_u32();
+        let mut expected = rng.clone();
+
+        for _ in 0..(DEFAULT_BUFFER_SIZE / 32) * 2 {
+            let values = rng.next_u32x8();
+            // Vectors are read straight from the buffer, so we can only compare
+            // against scalar draws when there is a full vector left in the buffer
+            if expected.buffer_index() + 32 > DEFAULT_BUFFER_SIZE {
+                expected.next_m256i();
+                continue;
+            }
+            for &v in values.iter() {
+                assert_eq!(v, expected.next_u32());
+            }
+        }
+    }
+
+    #[test]
+    #[parallel]
+    fn sample_f32x8() {
+        let mut rng = create_with_predefined_seed();
+        let mut expected = rng.clone();
+
+        for _ in 0..64 {
+            let values = rng.next_f32x8();
+            assert!(values.iter().all(|v| FLOAT_RANGE.contains(v)));
+            for &v in values.iter() {
+                assert_eq!(v, expected.next_f32());
+            }
+        }
+    }
+
+    #[test]
+    #[parallel]
+    fn fill_u64() {
+        let mut rng = super::Shishua::<256>::from_seed(*get_predefined_seed());
+        // Start at an offset that isnt vector aligned
+        rng.next_u32();
+        let mut expected = rng.clone();
+
+        for len in [0, 1, 3, 4, 5, 31, 32, 33, 100, 1000] {
+            let mut values = vec![0u64; len];
+            rng.fill_u64(&mut values);
+            for v in values {
+                assert_eq!(v, expected.next_u64());
+            }
+            assert_eq!(rng.buffer_index(), expected.buffer_index());
+        }
+    }
+
+    #[test]
+    #[parallel]
+    fn fill_f64() {
+        let mut rng = super::Shishua::<256>::from_seed(*get_predefined_seed());
+        rng.next_u32();
+        let mut expected = rng.clone();
+
+        for len in [0, 1, 3, 4, 5, 31, 32, 33, 100, 1000] {
+            let mut values = vec![0f64; len];
+            rng.fill_f64(&mut values);
+            for v in values {
+                assert!(DOUBLE_RANGE.contains(&v));
+                assert_eq!(v, expected.next_f64());
+            }
+            assert_eq!(rng.buffer_index(), expected.buffer_index());
+        }
+    }
+
+    #[test]
+    #[parallel]
+    fn fill_u32() {
+        let mut rng = super::Shishua::<256>::from_seed(*get_predefined_seed());
+        rng.next_u32();
+        let mut expected = rng.clone();
+
+        for len in [0, 1, 3, 7, 8, 9, 63, 64, 65, 100, 1000] {
+            let mut values = vec![0u32; len];
+            rng.fill_u32(&mut values);
+            for v in values {
+                assert_eq!(v, expected.next_u32());
+            }
+            assert_eq!(rng.buffer_index(), expected.buffer_index());
+        }
+    }
+
+    #[test]
+    #[parallel]
+    fn fill_f32() {
+        let mut rng = super::Shishua::<256>::from_seed(*get_predefined_seed());
+        rng.next_u32();
+        let mut expected = rng.clone();
+
+        for len in [0, 1, 3, 7, 8, 9, 63, 64, 65, 100, 1000] {
+            let mut values = vec![0f32; len];
+            rng.fill_f32(&mut values);
+            for v in values {
+                assert!(FLOAT_RANGE.contains(&v));
+                assert_eq!(v, expected.next_f32());
+            }
+            assert_eq!(rng.buffer_index(), expected.buffer_index());
+        }
+    }
+
+    #[test]
+    #[parallel]
+    fn fill_bytes_larger_than_buffer() {
+        let mut rng = super::Shishua::<256>::from_seed(*get_predefined_seed());
+        let mut expected = rng.clone();
+
+        let mut bytes = [0u8; 256 * 3 + 17];
+        rng.fill_bytes(&mut bytes);
+        for chunk in bytes.chunks(256) {
+            let mut expected_chunk = vec![0u8; chunk.len()];
+            expected.fill_bytes(&mut expected_chunk);
+            assert_eq!(chunk, &expected_chunk[..]);
+        }
+    }
+
+    #[test]
+    #[parallel]
+    fn sample_f32x8_distribution() {
+        let mut rng = create_with_zero_seed();
+
+        let mut current: Option<F32x8> = None;
+        let mut current_index: usize = 0;
+
+        test_uniform_distribution::<10_000_000, f32>(
+            || match &current {
+                Some(vector) if current_index < 8 => {
+                    let result = vector[current_index];
+                    current_index += 1;
+                    return result;
+                }
+                _ => {
+                    current_index = 0;
+                    let vector = rng.next_f32x8();
+                    let result = vector[current_index];
+                    current = Some(vector);
+                    current_index += 1;
+                    return result;
+                }
+            },
+            FLOAT_RANGE,
+        );
+    }
+
     #[test]
     #[parallel]
     fn sample_f64_distribution() {
@@ -638,4 +2595,62 @@ mod tests {
         0x08, 0xb7, 0xbf, 0x54, 0x6e, 0x09, 0x29, 0x39, 0xf2, 0x53, 0xaa, 0x49, 0x81, 0xb2, 0x14, 0xee, 0xd2, 0x52,
         0x68, 0x4b, 0xe3, 0xc0, 0x4e, 0x1b, 0x75, 0xed,
     ];
+
+    #[test]
+    #[parallel]
+    fn split() {
+        fn split_depth_first<R: SplittableRng>(rng: R, depth: u32, leaves: &mut Vec<R>) {
+            if depth == 0 {
+                leaves.push(rng);
+                return;
+            }
+            let (left, right) = rng.split();
+            split_depth_first(left, depth - 1, leaves);
+            split_depth_first(right, depth - 1, leaves);
+        }
+
+        fn split_breadth_first<R: SplittableRng>(rng: R, depth: u32) -> Vec<R> {
+            let mut leaves = vec![rng];
+            for _ in 0..depth {
+                leaves = leaves
+                    .into_iter()
+                    .flat_map(|rng| {
+                        let (left, right) = rng.split();
+                        [left, right]
+                    })
+                    .collect();
+            }
+            leaves
+        }
+
+        let seed: [u8; 32] = rand::random();
+        let mut rng = RngImpl::from_seed(seed);
+        rng.next_u32();
+
+        let mut depth_first = Vec::new();
+        split_depth_first(rng.clone(), 3, &mut depth_first);
+        let leaves = depth_first.iter_mut().map(|rng| rng.next_u64()).collect_vec();
+        assert_eq!(leaves.iter().unique().count(), leaves.len());
+
+        let mut inline = ShishuaInline::<DEFAULT_BUFFER_SIZE>::from_seed(seed);
+        inline.next_u32();
+        let mut dyn_rng = ShishuaDyn::from_seed_with_buffer_size(seed, DEFAULT_BUFFER_SIZE);
+        dyn_rng.next_u32();
+        let inline_leaves = split_breadth_first(inline, 3)
+            .iter_mut()
+            .map(|rng| rng.next_u64())
+            .collect_vec();
+        let dyn_leaves = split_breadth_first(dyn_rng, 3)
+            .iter_mut()
+            .map(|rng| rng.next_u64())
+            .collect_vec();
+        assert_eq!(inline_leaves, leaves);
+        assert_eq!(dyn_leaves, leaves);
+
+        // The first split reseeds from the 32 bytes following the value drawn before
+        let (mut left, mut right) = rng.clone().split();
+        let (left_seed, right_seed) = split_seeds(&mut rng);
+        assert_eq!(left.next_u64(), RngImpl::from_seed(left_seed).next_u64());
+        assert_eq!(right.next_u64(), RngImpl::from_seed(right_seed).next_u64());
+    }
 }
@@ -1,6 +1,6 @@
 use std::{
     arch::{asm, x86_64::*},
-    mem::transmute,
+    mem::{self, transmute},
 };
 
 use super::vecs::*;
@@ -13,16 +13,16 @@ pub trait SimdRand {
         unsafe {
             let v = self.next_m256i();
 
-            let lhs = m256i_to_m256d(_mm256_srli_epi64::<11>(v));
+            m256i_to_unit_m256d(v)
+        }
+    }
 
-            // PERF: This is precomputed based on the constants from the formula above
-            // I found no other efficient (and succint) constant way of representing the RHS.
-            // setzero and constants used in shifts like 11 above are automatically constant folded.
-            // Writing out the actual formula ended up not being constant folded by the compiler.
-            const RHS_FACTOR: [f64; 4] = [1.1102230246251565E-16; 4];
-            const RHS: __m256d = unsafe { transmute::<[f64; 4], __m256d>(RHS_FACTOR) };
+    #[inline(always)]
+    fn next_m256(&mut self) -> __m256 {
+        unsafe {
+            let v = self.next_m256i();
 
-            _mm256_mul_pd(lhs, RHS)
+            m256i_to_unit_m256(v)
         }
     }
 
@@ -45,6 +45,142 @@ pub trait SimdRand {
             vector
         }
     }
+
+    #[inline(always)]
+    fn next_u32x8(&mut self) -> U32x8 {
+        unsafe {
+            let v = self.next_m256i();
+            let mut vector: U32x8 = Default::default();
+            _mm256_store_si256(vector.as_mut_ptr() as *mut __m256i, v);
+            vector
+        }
+    }
+
+    #[inline(always)]
+    fn next_f32x8(&mut self) -> F32x8 {
+        unsafe {
+            let v = self.next_m256();
+            let mut vector: F32x8 = Default::default();
+            _mm256_store_ps(vector.as_mut_ptr(), v);
+            vector
+        }
+    }
+
+    /// Fills `dest` with the values of consecutive [`SimdRand::next_m256i`] vectors,
+    /// lane `i` of a vector being element `i`, regardless of the alignment of `dest`.
+    /// The unused lanes of the last vector are discarded, so the next call starts with a new vector.
+    #[inline(always)]
+    fn fill_u64(&mut self, dest: &mut [u64]) {
+        fill::<_, 4>(dest, || self.next_m256i());
+    }
+
+    /// Fills `dest` with the values of [`SimdRand::next_m256d`], see [`SimdRand::fill_u64`].
+    #[inline(always)]
+    fn fill_f64(&mut self, dest: &mut [f64]) {
+        fill::<_, 4>(dest, || unsafe { _mm256_castpd_si256(self.next_m256d()) });
+    }
+
+    /// Fills `dest` with the values of [`SimdRand::next_m256i`] as 32bit lanes, see [`SimdRand::fill_u64`].
+    #[inline(always)]
+    fn fill_u32(&mut self, dest: &mut [u32]) {
+        fill::<_, 8>(dest, || self.next_m256i());
+    }
+
+    /// Fills `dest` with the values of [`SimdRand::next_m256`], see [`SimdRand::fill_u64`].
+    #[inline(always)]
+    fn fill_f32(&mut self, dest: &mut [f32]) {
+        fill::<_, 8>(dest, || unsafe { _mm256_castps_si256(self.next_m256()) });
+    }
+}
+
+/// Fills `dest` with the elements of the vectors returned by `next`, in order,
+/// each vector holding `L` elements of type `T`.
+/// The elements up to the first 32 byte boundary of `dest` are written one at a time,
+/// the body with aligned stores and the tail one at a time again.
+/// When the head doesn't take a whole vector, every vector of the body is
+/// the remaining lanes of the previous vector followed by the first lanes of the next one,
+/// so the output doesn't depend on the alignment of `dest`.
+#[inline(always)]
+fn fill<T: Copy, const L: usize>(dest: &mut [T], mut next: impl FnMut() -> __m256i) {
+    const { assert!(mem::size_of::<__m256i>() == L * mem::size_of::<T>()) };
+
+    // SAFETY: `T` is a plain number, `L` of which make up a vector, so any bits are valid for both
+    let (head, body, tail) = unsafe { dest.align_to_mut::<__m256i>() };
+
+    // The last vector drawn and the number of its elements written so far
+    let mut carry = unsafe { _mm256_setzero_si256() };
+    let mut used = L;
+
+    fill_elements::<T, L>(head, &mut carry, &mut used, &mut next);
+    unsafe {
+        if used == L {
+            for vector in body {
+                _mm256_store_si256(vector, next());
+            }
+        } else {
+            // 32bit index of the first unused element of `carry`
+            let offset = (used * mem::size_of::<T>() / mem::size_of::<u32>()) as i32;
+            let index = _mm256_add_epi32(_mm256_setr_epi32(0, 1, 2, 3, 4, 5, 6, 7), _mm256_set1_epi32(offset));
+            let from_next = _mm256_cmpgt_epi32(index, _mm256_set1_epi32(7));
+            for vector in body {
+                let v = next();
+                let lo = _mm256_permutevar8x32_epi32(carry, index);
+                let hi = _mm256_permutevar8x32_epi32(v, index);
+                _mm256_store_si256(vector, _mm256_blendv_epi8(lo, hi, from_next));
+                carry = v;
+            }
+        }
+    }
+    fill_elements::<T, L>(tail, &mut carry, &mut used, &mut next);
+}
+
+/// Writes `dest` one element at a time, continuing with the unused elements of `carry`
+/// and drawing a new vector from `next` whenever they run out.
+#[inline(always)]
+fn fill_elements<T: Copy, const L: usize>(
+    dest: &mut [T],
+    carry: &mut __m256i,
+    used: &mut usize,
+    next: &mut impl FnMut() -> __m256i,
+) {
+    for value in dest {
+        if *used == L {
+            *carry = next();
+            *used = 0;
+        }
+        // SAFETY: see `fill`
+        let elements = unsafe { mem::transmute_copy::<__m256i, [T; L]>(carry) };
+        *value = elements[*used];
+        *used += 1;
+    }
+}
+
+#[inline(always)]
+pub(super) unsafe fn m256i_to_unit_m256d(v: __m256i) -> __m256d {
+    let lhs = m256i_to_m256d(_mm256_srli_epi64::<11>(v));
+
+    // PERF: This is precomputed based on the constants from the formula above
+    // I found no other efficient (and succint) constant way of representing the RHS.
+    // setzero and constants used in shifts like 11 above are automatically constant folded.
+    // Writing out the actual formula ended up not being constant folded by the compiler.
+    const RHS_FACTOR: [f64; 4] = [1.1102230246251565E-16; 4];
+    const RHS: __m256d = unsafe { transmute::<[f64; 4], __m256d>(RHS_FACTOR) };
+
+    _mm256_mul_pd(lhs, RHS)
+}
+
+/// Converts 8 random u32's to f32's in the range [0, 1)
+#[inline(always)]
+pub(super) unsafe fn m256i_to_unit_m256(v: __m256i) -> __m256 {
+    // After the shift all values fit in 24 bits,
+    // so the signed conversion is exact
+    let lhs = _mm256_cvtepi32_ps(_mm256_srli_epi32::<8>(v));
+
+    // 1.0 / (1 << 24)
+    const RHS_FACTOR: [f32; 8] = [5.9604645E-8; 8];
+    const RHS: __m256 = unsafe { transmute::<[f32; 8], __m256>(RHS_FACTOR) };
+
+    _mm256_mul_ps(lhs, RHS)
 }
 
 #[inline(always)]
@@ -93,3 +229,73 @@ unsafe fn m256i_to_m256d(v: __m256i) -> __m256d {
     let result = _mm256_add_pd(v_hi_dbl, _mm256_castsi256_pd(v_lo));
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use rand_core::{RngCore, SeedableRng};
+    use serial_test::parallel;
+
+    use crate::specific::avx2::{Shishua, Xoshiro256PlusPlusX4};
+    use crate::testutil::vector_stream;
+
+    use super::*;
+
+    const OFFSETS: usize = 16;
+    const LENS: [usize; 6] = [0, 1, 3, 9, 33, 100];
+
+    #[test]
+    #[parallel]
+    fn fills() {
+        let seed = rand::random();
+        for offset in 0..OFFSETS {
+            for len in LENS {
+                let mut rng = Xoshiro256PlusPlusX4::from_scalar_seed(seed);
+                let mut expected = Xoshiro256PlusPlusX4::from_scalar_seed(seed);
+
+                let mut values = vec![0u64; offset + len];
+                rng.fill_u64(&mut values[offset..]);
+                let stream = vector_stream(len, || expected.next_u64x4().to_vec());
+                assert_eq!(&values[offset..], &stream[..]);
+
+                let mut values = vec![0f64; offset + len];
+                rng.fill_f64(&mut values[offset..]);
+                let stream = vector_stream(len, || expected.next_f64x4().to_vec());
+                assert_eq!(&values[offset..], &stream[..]);
+
+                let mut values = vec![0u32; offset + len];
+                rng.fill_u32(&mut values[offset..]);
+                let stream = vector_stream(len, || expected.next_u32x8().to_vec());
+                assert_eq!(&values[offset..], &stream[..]);
+
+                let mut values = vec![0f32; offset + len];
+                rng.fill_f32(&mut values[offset..]);
+                let stream = vector_stream(len, || expected.next_f32x8().to_vec());
+                assert_eq!(&values[offset..], &stream[..]);
+            }
+        }
+    }
+
+    #[test]
+    #[parallel]
+    fn shishua_fills_from_buffer() {
+        let seed = rand::random();
+        let mut rng = Shishua::<256>::from_seed(seed);
+        let mut expected = Shishua::<256>::from_seed(seed);
+
+        let mut values = vec![0u64; 101];
+        SimdRand::fill_u64(&mut rng, &mut values[1..]);
+        assert!(values[1..].iter().all(|&v| v == expected.next_u64()));
+
+        let mut values = vec![0f64; 101];
+        SimdRand::fill_f64(&mut rng, &mut values[1..]);
+        assert!(values[1..].iter().all(|&v| v == expected.next_f64()));
+
+        let mut values = vec![0u32; 101];
+        SimdRand::fill_u32(&mut rng, &mut values[1..]);
+        assert!(values[1..].iter().all(|&v| v == expected.next_u32()));
+
+        let mut values = vec![0f32; 101];
+        SimdRand::fill_f32(&mut rng, &mut values[1..]);
+        assert!(values[1..].iter().all(|&v| v == expected.next_f32()));
+    }
+}
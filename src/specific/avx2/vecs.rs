@@ -8,6 +8,14 @@ pub struct U64x4([u64; 4]);
 #[repr(align(32))]
 pub struct F64x4([f64; 4]);
 
+#[derive(Default, Debug)]
+#[repr(align(32))]
+pub struct U32x8([u32; 8]);
+
+#[derive(Default, Debug)]
+#[repr(align(32))]
+pub struct F32x8([f32; 8]);
+
 impl U64x4 {
     #[inline(always)]
     pub fn new(values: [u64; 4]) -> Self {
@@ -22,6 +30,20 @@ impl F64x4 {
     }
 }
 
+impl U32x8 {
+    #[inline(always)]
+    pub fn new(values: [u32; 8]) -> Self {
+        Self(values)
+    }
+}
+
+impl F32x8 {
+    #[inline(always)]
+    pub fn new(values: [f32; 8]) -> Self {
+        Self(values)
+    }
+}
+
 impl Deref for U64x4 {
     type Target = [u64; 4];
 
@@ -60,6 +82,44 @@ impl Into<F64x4> for [f64; 4] {
     }
 }
 
+impl Deref for U32x8 {
+    type Target = [u32; 8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+impl DerefMut for U32x8 {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl Into<U32x8> for [u32; 8] {
+    fn into(self) -> U32x8 {
+        U32x8::new(self)
+    }
+}
+
+impl Deref for F32x8 {
+    type Target = [f32; 8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+impl DerefMut for F32x8 {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl Into<F32x8> for [f32; 8] {
+    fn into(self) -> F32x8 {
+        F32x8::new(self)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::{arch::x86_64::*, mem::size_of};
@@ -73,5 +133,7 @@ mod tests {
     fn size() {
         assert_eq!(size_of::<__m256i>(), size_of::<U64x4>());
         assert_eq!(size_of::<__m256d>(), size_of::<F64x4>());
+        assert_eq!(size_of::<__m256i>(), size_of::<U32x8>());
+        assert_eq!(size_of::<__m256>(), size_of::<F32x8>());
     }
 }
@@ -1,12 +1,10 @@
 use std::{
     arch::x86_64::*,
-    mem,
     ops::{Deref, DerefMut},
 };
 
-use rand_core::SeedableRng;
-
-use crate::specific::avx2::read_u64_into_vec;
+use crate::specific::avx2::{read_u64_into_vec, u64_lanes_from_vec, u64_lanes_into_vec, write_u64_lanes_into_seed};
+use crate::xoshiro::{self, LaneBuffer};
 
 use super::{rotate_left, simdrand::*};
 
@@ -62,22 +60,44 @@ pub struct Xoshiro256PlusX4 {
     s1: __m256i,
     s2: __m256i,
     s3: __m256i,
+    // Number of splits leading to this generator, see `SplittableRng`
+    depth: u8,
+    // Lanes not yet returned through `RngCore`
+    buffer: LaneBuffer<4>,
 }
 
-impl SeedableRng for Xoshiro256PlusX4 {
-    type Seed = Xoshiro256PlusX4Seed;
+xoshiro::vector_xoshiro! {
+    impl Xoshiro256PlusX4 {
+        seed: Xoshiro256PlusX4Seed,
+        lanes: 4,
+        scalar: Xoshiro256Plus,
+        read_seed_lanes: |src| u64_lanes_from_vec(read_u64_into_vec(src)),
+        write_seed_lanes: write_u64_lanes_into_seed,
+    }
+}
 
-    fn from_seed(seed: Self::Seed) -> Self {
-        const SIZE: usize = mem::size_of::<u64>();
-        const LEN: usize = 4;
-        const VECSIZE: usize = SIZE * LEN;
+impl Xoshiro256PlusX4 {
+    /// Vector states, `states[k][i]` being word `k` of the state of lane `i`.
+    fn from_states(states: [[u64; 4]; 4]) -> Self {
+        let [s0, s1, s2, s3] = states.map(u64_lanes_into_vec);
+
+        Self {
+            s0,
+            s1,
+            s2,
+            s3,
+            depth: 0,
+            buffer: LaneBuffer::new(),
+        }
+    }
 
-        let s0 = read_u64_into_vec(&seed[(VECSIZE * 0)..(VECSIZE * 1)]);
-        let s1 = read_u64_into_vec(&seed[(VECSIZE * 1)..(VECSIZE * 2)]);
-        let s2 = read_u64_into_vec(&seed[(VECSIZE * 2)..(VECSIZE * 3)]);
-        let s3 = read_u64_into_vec(&seed[(VECSIZE * 3)..(VECSIZE * 4)]);
+    fn states(&self) -> [[u64; 4]; 4] {
+        [self.s0, self.s1, self.s2, self.s3].map(u64_lanes_from_vec)
+    }
 
-        Self { s0, s1, s2, s3 }
+    #[inline(always)]
+    fn next_lanes(&mut self) -> [u64; 4] {
+        u64_lanes_from_vec(self.next_m256i())
     }
 }
 
@@ -109,7 +129,7 @@ mod tests {
     use rand_core::{RngCore, SeedableRng};
     use serial_test::parallel;
 
-    use crate::testutil::{test_uniform_distribution, DOUBLE_RANGE, REF_SEED_256};
+    use crate::testutil::{test_uniform_distribution, xoshiro_tests, DOUBLE_RANGE, REF_SEED_256};
 
     use super::super::vecs::*;
     use super::*;
@@ -117,6 +137,14 @@ mod tests {
     type RngSeed = Xoshiro256PlusX4Seed;
     type RngImpl = Xoshiro256PlusX4;
 
+    xoshiro_tests!(
+        Xoshiro256PlusX4,
+        seed: Xoshiro256PlusX4Seed,
+        scalar: rand_xoshiro::Xoshiro256Plus,
+        lanes: 4,
+        portable: crate::portable::Xoshiro256PlusX<16>,
+    );
+
     #[test]
     #[parallel]
     fn reference() {
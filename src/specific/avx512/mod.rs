@@ -32,3 +32,24 @@ fn read_u64_into_vec(src: &[u8]) -> __m512i {
         )
     }
 }
+
+/// Writes lanes into seed bytes, the inverse of [`read_u64_into_vec`] which loads the last lane first.
+fn write_u64_lanes_into_seed(lanes: [u64; 8], dst: &mut [u8]) {
+    for (chunk, lane) in dst.chunks_exact_mut(mem::size_of::<u64>()).zip(lanes.into_iter().rev()) {
+        chunk.copy_from_slice(&lane.to_le_bytes());
+    }
+}
+
+/// Loads lanes into a vector, lane `i` of the vector being `lanes[i]`.
+#[inline(always)]
+fn u64_lanes_into_vec(lanes: [u64; 8]) -> __m512i {
+    unsafe { _mm512_loadu_si512(lanes.as_ptr().cast()) }
+}
+
+/// Stores the lanes of a vector, the inverse of [`u64_lanes_into_vec`].
+#[inline(always)]
+fn u64_lanes_from_vec(vector: __m512i) -> [u64; 8] {
+    let mut lanes = [0; 8];
+    unsafe { _mm512_storeu_si512(lanes.as_mut_ptr().cast(), vector) };
+    lanes
+}
@@ -1,6 +1,6 @@
 use std::{
     arch::{asm, x86_64::*},
-    mem::transmute,
+    mem::{self, transmute},
 };
 
 use super::vecs::*;
@@ -26,6 +26,24 @@ pub trait SimdRand {
         }
     }
 
+    /// Random f32's in the range [0, 1), converted from the 16 32bit lanes of [`SimdRand::next_m512i`]
+    #[inline(always)]
+    fn next_m512(&mut self) -> __m512 {
+        unsafe {
+            let v = self.next_m512i();
+
+            // After the shift all values fit in 24 bits,
+            // so the signed conversion is exact
+            let lhs = _mm512_cvtepi32_ps(_mm512_srli_epi32::<8>(v));
+
+            // 1.0 / (1 << 24)
+            const RHS_FACTOR: [f32; 16] = [5.9604645E-8; 16];
+            const RHS: __m512 = unsafe { transmute::<[f32; 16], __m512>(RHS_FACTOR) };
+
+            _mm512_mul_ps(lhs, RHS)
+        }
+    }
+
     #[inline(always)]
     fn next_u64x8(&mut self) -> U64x8 {
         unsafe {
@@ -45,6 +63,94 @@ pub trait SimdRand {
             vector
         }
     }
+
+    /// Fills `dest` with the values of consecutive [`SimdRand::next_m512i`] vectors,
+    /// lane `i` of a vector being element `i`, regardless of the alignment of `dest`.
+    /// The unused lanes of the last vector are discarded, so the next call starts with a new vector.
+    #[inline(always)]
+    fn fill_u64(&mut self, dest: &mut [u64]) {
+        fill::<_, 8>(dest, || self.next_m512i());
+    }
+
+    /// Fills `dest` with the values of [`SimdRand::next_m512d`], see [`SimdRand::fill_u64`].
+    #[inline(always)]
+    fn fill_f64(&mut self, dest: &mut [f64]) {
+        fill::<_, 8>(dest, || unsafe { _mm512_castpd_si512(self.next_m512d()) });
+    }
+
+    /// Fills `dest` with the values of [`SimdRand::next_m512i`] as 32bit lanes, see [`SimdRand::fill_u64`].
+    #[inline(always)]
+    fn fill_u32(&mut self, dest: &mut [u32]) {
+        fill::<_, 16>(dest, || self.next_m512i());
+    }
+
+    /// Fills `dest` with the values of [`SimdRand::next_m512`], see [`SimdRand::fill_u64`].
+    #[inline(always)]
+    fn fill_f32(&mut self, dest: &mut [f32]) {
+        fill::<_, 16>(dest, || unsafe { _mm512_castps_si512(self.next_m512()) });
+    }
+}
+
+/// Fills `dest` with the elements of the vectors returned by `next`, in order,
+/// each vector holding `L` elements of type `T`.
+/// The elements up to the first 64 byte boundary of `dest` are written one at a time,
+/// the body with aligned stores and the tail one at a time again.
+/// When the head doesn't take a whole vector, every vector of the body is
+/// the remaining lanes of the previous vector followed by the first lanes of the next one,
+/// so the output doesn't depend on the alignment of `dest`.
+#[inline(always)]
+fn fill<T: Copy, const L: usize>(dest: &mut [T], mut next: impl FnMut() -> __m512i) {
+    const { assert!(mem::size_of::<__m512i>() == L * mem::size_of::<T>()) };
+
+    // SAFETY: `T` is a plain number, `L` of which make up a vector, so any bits are valid for both
+    let (head, body, tail) = unsafe { dest.align_to_mut::<__m512i>() };
+
+    // The last vector drawn and the number of its elements written so far
+    let mut carry = unsafe { _mm512_setzero_si512() };
+    let mut used = L;
+
+    fill_elements::<T, L>(head, &mut carry, &mut used, &mut next);
+    unsafe {
+        if used == L {
+            for vector in body {
+                _mm512_store_si512(vector, next());
+            }
+        } else {
+            // 32bit index of the first unused element of `carry`, indices from 16 on select from the next vector
+            let offset = (used * mem::size_of::<T>() / mem::size_of::<u32>()) as i32;
+            let index = _mm512_add_epi32(
+                _mm512_setr_epi32(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15),
+                _mm512_set1_epi32(offset),
+            );
+            for vector in body {
+                let v = next();
+                _mm512_store_si512(vector, _mm512_permutex2var_epi32(carry, index, v));
+                carry = v;
+            }
+        }
+    }
+    fill_elements::<T, L>(tail, &mut carry, &mut used, &mut next);
+}
+
+/// Writes `dest` one element at a time, continuing with the unused elements of `carry`
+/// and drawing a new vector from `next` whenever they run out.
+#[inline(always)]
+fn fill_elements<T: Copy, const L: usize>(
+    dest: &mut [T],
+    carry: &mut __m512i,
+    used: &mut usize,
+    next: &mut impl FnMut() -> __m512i,
+) {
+    for value in dest {
+        if *used == L {
+            *carry = next();
+            *used = 0;
+        }
+        // SAFETY: see `fill`
+        let elements = unsafe { mem::transmute_copy::<__m512i, [T; L]>(carry) };
+        *value = elements[*used];
+        *used += 1;
+    }
 }
 
 #[inline(always)]
@@ -67,3 +173,53 @@ unsafe fn m512i_to_m512d(src: __m512i) -> __m512d {
     );
     dst
 }
+
+#[cfg(test)]
+mod tests {
+    use serial_test::parallel;
+
+    use crate::specific::avx512::Xoshiro256PlusPlusX8;
+    use crate::testutil::vector_stream;
+
+    use super::*;
+
+    const OFFSETS: usize = 32;
+    const LENS: [usize; 6] = [0, 1, 7, 17, 65, 100];
+
+    #[test]
+    #[parallel]
+    fn fills() {
+        let seed = rand::random();
+        for offset in 0..OFFSETS {
+            for len in LENS {
+                let mut rng = Xoshiro256PlusPlusX8::from_scalar_seed(seed);
+                let mut expected = Xoshiro256PlusPlusX8::from_scalar_seed(seed);
+
+                let mut values = vec![0u64; offset + len];
+                rng.fill_u64(&mut values[offset..]);
+                let stream = vector_stream(len, || expected.next_u64x8().to_vec());
+                assert_eq!(&values[offset..], &stream[..]);
+
+                let mut values = vec![0f64; offset + len];
+                rng.fill_f64(&mut values[offset..]);
+                let stream = vector_stream(len, || expected.next_f64x8().to_vec());
+                assert_eq!(&values[offset..], &stream[..]);
+
+                let mut values = vec![0u32; offset + len];
+                rng.fill_u32(&mut values[offset..]);
+                let stream = vector_stream(len, || {
+                    unsafe { transmute::<_, [u32; 16]>(expected.next_m512i()) }.to_vec()
+                });
+                assert_eq!(&values[offset..], &stream[..]);
+
+                let mut values = vec![0f32; offset + len];
+                rng.fill_f32(&mut values[offset..]);
+                let stream = vector_stream(len, || {
+                    let u32s = unsafe { transmute::<_, [u32; 16]>(expected.next_m512i()) };
+                    u32s.map(|v| (v >> 8) as f32 * (1.0 / (1u32 << 24) as f32)).to_vec()
+                });
+                assert_eq!(&values[offset..], &stream[..]);
+            }
+        }
+    }
+}
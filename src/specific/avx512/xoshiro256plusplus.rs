@@ -1,12 +1,10 @@
 use std::{
     arch::x86_64::*,
-    mem,
     ops::{Deref, DerefMut},
 };
 
-use rand_core::SeedableRng;
-
-use crate::specific::avx512::read_u64_into_vec;
+use crate::specific::avx512::{read_u64_into_vec, u64_lanes_from_vec, u64_lanes_into_vec, write_u64_lanes_into_seed};
+use crate::xoshiro::{self, LaneBuffer};
 
 use super::simdrand::*;
 
@@ -50,6 +48,10 @@ pub struct Xoshiro256PlusPlusX8 {
     s1: __m512i,
     s2: __m512i,
     s3: __m512i,
+    // Number of splits leading to this generator, see `SplittableRng`
+    depth: u8,
+    // Lanes not yet returned through `RngCore`
+    buffer: LaneBuffer<8>,
 }
 impl Default for Xoshiro256PlusPlusX8Seed {
     fn default() -> Xoshiro256PlusPlusX8Seed {
@@ -63,21 +65,38 @@ impl AsMut<[u8]> for Xoshiro256PlusPlusX8Seed {
     }
 }
 
-impl SeedableRng for Xoshiro256PlusPlusX8 {
-    type Seed = Xoshiro256PlusPlusX8Seed;
+xoshiro::vector_xoshiro! {
+    impl Xoshiro256PlusPlusX8 {
+        seed: Xoshiro256PlusPlusX8Seed,
+        lanes: 8,
+        scalar: Xoshiro256PlusPlus,
+        read_seed_lanes: |src| u64_lanes_from_vec(read_u64_into_vec(src)),
+        write_seed_lanes: write_u64_lanes_into_seed,
+    }
+}
 
-    fn from_seed(seed: Self::Seed) -> Self {
-        const SIZE: usize = mem::size_of::<u64>();
-        const LEN: usize = 8;
-        const VECSIZE: usize = SIZE * LEN;
-        // TODO: implement "jumps" between lanes?
+impl Xoshiro256PlusPlusX8 {
+    /// Vector states, `states[k][i]` being word `k` of the state of lane `i`.
+    fn from_states(states: [[u64; 8]; 4]) -> Self {
+        let [s0, s1, s2, s3] = states.map(u64_lanes_into_vec);
+
+        Self {
+            s0,
+            s1,
+            s2,
+            s3,
+            depth: 0,
+            buffer: LaneBuffer::new(),
+        }
+    }
 
-        let s0 = read_u64_into_vec(&seed[(VECSIZE * 0)..(VECSIZE * 1)]);
-        let s1 = read_u64_into_vec(&seed[(VECSIZE * 1)..(VECSIZE * 2)]);
-        let s2 = read_u64_into_vec(&seed[(VECSIZE * 2)..(VECSIZE * 3)]);
-        let s3 = read_u64_into_vec(&seed[(VECSIZE * 3)..(VECSIZE * 4)]);
+    fn states(&self) -> [[u64; 8]; 4] {
+        [self.s0, self.s1, self.s2, self.s3].map(u64_lanes_from_vec)
+    }
 
-        Self { s0, s1, s2, s3 }
+    #[inline(always)]
+    fn next_lanes(&mut self) -> [u64; 8] {
+        u64_lanes_from_vec(self.next_m512i())
     }
 }
 
@@ -117,7 +136,7 @@ mod tests {
     use rand_core::{RngCore, SeedableRng};
     use serial_test::parallel;
 
-    use crate::testutil::{test_uniform_distribution, DOUBLE_RANGE, REF_SEED_512};
+    use crate::testutil::{test_uniform_distribution, xoshiro_tests, DOUBLE_RANGE, REF_SEED_512};
 
     use super::super::vecs::*;
     use super::*;
@@ -125,6 +144,14 @@ mod tests {
     type RngSeed = Xoshiro256PlusPlusX8Seed;
     type RngImpl = Xoshiro256PlusPlusX8;
 
+    xoshiro_tests!(
+        Xoshiro256PlusPlusX8,
+        seed: Xoshiro256PlusPlusX8Seed,
+        scalar: rand_xoshiro::Xoshiro256PlusPlus,
+        lanes: 8,
+        portable: crate::portable::Xoshiro256PlusPlusX<16>,
+    );
+
     #[test]
     #[parallel]
     fn reference() {
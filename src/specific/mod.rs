@@ -8,3 +8,6 @@ pub mod avx2;
     target_feature = "avx512vl"
 ))]
 pub mod avx512;
+
+#[cfg(target_arch = "x86_64")]
+pub mod rdrand;
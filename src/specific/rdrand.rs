@@ -0,0 +1,154 @@
+//! Seeding from the CPU's hardware random number generator through the `RDSEED` and `RDRAND` instructions.
+//!
+//! `RDSEED` reads the conditioned entropy source directly and is preferred for seeding,
+//! `RDRAND` reads a DRBG reseeded from that source. Both may transiently fail under contention,
+//! so every read is retried a bounded number of times. If an instruction is unavailable or keeps failing,
+//! [`fill_bytes`] moves on to the next source, ending with the OS entropy source.
+//!
+//! ```rust
+//! use rand_core::SeedableRng;
+//! use simd_rand::portable::*;
+//! use simd_rand::specific::rdrand;
+//!
+//! let seed: Xoshiro256PlusPlusX8Seed = rdrand::seed().unwrap();
+//! let mut rng = Xoshiro256PlusPlusX8::from_seed(seed);
+//! ```
+
+use std::arch::x86_64::*;
+use std::mem;
+
+use crate::seeding::fill_from_os_entropy;
+
+/// Number of attempts per 64-bit read before giving up on an instruction.
+/// Intel recommends 10 retries for `RDRAND`, `RDSEED` may need more as the entropy source is slower.
+const RDRAND_RETRIES: usize = 10;
+const RDSEED_RETRIES: usize = 100;
+
+/// The source which ended up filling the buffer in [`fill_bytes`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EntropySource {
+    RdSeed,
+    RdRand,
+    Os,
+}
+
+/// Fills `dest` using `RDSEED`, falling back to `RDRAND` and then to the OS entropy source.
+/// Returns the source that was used, the whole buffer always comes from a single source.
+pub fn fill_bytes(dest: &mut [u8]) -> Result<EntropySource, rand_core::Error> {
+    fill_with(
+        dest,
+        is_x86_feature_detected!("rdseed"),
+        is_x86_feature_detected!("rdrand"),
+    )
+}
+
+/// Creates a seed through [`fill_bytes`].
+pub fn seed<S: Default + AsMut<[u8]>>() -> Result<S, rand_core::Error> {
+    let mut seed = S::default();
+    fill_bytes(seed.as_mut())?;
+    Ok(seed)
+}
+
+fn fill_with(dest: &mut [u8], rdseed: bool, rdrand: bool) -> Result<EntropySource, rand_core::Error> {
+    // SAFETY: the instructions are only used if they were detected at runtime
+    if rdseed && unsafe { fill_with_step(dest, rdseed_step, RDSEED_RETRIES) } {
+        return Ok(EntropySource::RdSeed);
+    }
+    if rdrand && unsafe { fill_with_step(dest, rdrand_step, RDRAND_RETRIES) } {
+        return Ok(EntropySource::RdRand);
+    }
+    fill_from_os_entropy(dest)?;
+    Ok(EntropySource::Os)
+}
+
+#[inline(always)]
+unsafe fn fill_with_step(dest: &mut [u8], step: unsafe fn() -> Option<u64>, retries: usize) -> bool {
+    for chunk in dest.chunks_mut(mem::size_of::<u64>()) {
+        let Some(value) = retry(step, retries) else {
+            return false;
+        };
+        chunk.copy_from_slice(&value.to_le_bytes()[..chunk.len()]);
+    }
+    true
+}
+
+#[inline(always)]
+unsafe fn retry(step: unsafe fn() -> Option<u64>, retries: usize) -> Option<u64> {
+    for _ in 0..retries {
+        match step() {
+            // Some AMD CPUs report success while always returning all ones after suspend/resume
+            Some(u64::MAX) | None => _mm_pause(),
+            value => return value,
+        }
+    }
+    None
+}
+
+#[target_feature(enable = "rdseed")]
+unsafe fn rdseed_step() -> Option<u64> {
+    let mut value = 0;
+    (_rdseed64_step(&mut value) == 1).then_some(value)
+}
+
+#[target_feature(enable = "rdrand")]
+unsafe fn rdrand_step() -> Option<u64> {
+    let mut value = 0;
+    (_rdrand64_step(&mut value) == 1).then_some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use serial_test::parallel;
+
+    use super::*;
+
+    fn assert_filled(source: EntropySource, rdseed: bool, rdrand: bool) {
+        // Odd length to cover the partial last chunk
+        let mut a = [0u8; 37];
+        let mut b = [0u8; 37];
+        assert_eq!(fill_with(&mut a, rdseed, rdrand).unwrap(), source);
+        assert_eq!(fill_with(&mut b, rdseed, rdrand).unwrap(), source);
+        assert!(a.iter().any(|&b| b != 0));
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    #[parallel]
+    fn sources() {
+        let rdseed = is_x86_feature_detected!("rdseed");
+        let rdrand = is_x86_feature_detected!("rdrand");
+
+        if rdseed {
+            assert_filled(EntropySource::RdSeed, true, rdrand);
+        }
+        if rdrand {
+            assert_filled(EntropySource::RdRand, false, true);
+        }
+        assert_filled(EntropySource::Os, false, false);
+    }
+
+    #[test]
+    #[parallel]
+    fn failing_instruction_falls_back() {
+        unsafe fn failing_step() -> Option<u64> {
+            None
+        }
+        unsafe fn all_ones_step() -> Option<u64> {
+            Some(u64::MAX)
+        }
+
+        let mut bytes = [0u8; 16];
+        unsafe {
+            assert!(!fill_with_step(&mut bytes, failing_step, RDRAND_RETRIES));
+            assert!(!fill_with_step(&mut bytes, all_ones_step, RDRAND_RETRIES));
+        }
+    }
+
+    #[test]
+    #[parallel]
+    fn seed() {
+        let a: [u8; 32] = super::seed().unwrap();
+        let b: [u8; 32] = super::seed().unwrap();
+        assert_ne!(a, b);
+    }
+}
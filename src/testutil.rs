@@ -1,10 +1,381 @@
-use std::{fmt::Debug, fmt::Display, ops::Range};
+use std::{fmt::Debug, fmt::Display, mem, ops::Range};
 
 use num_traits::{Num, NumCast};
+use rand_core::SeedableRng;
 use rust_decimal::prelude::*;
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 
+/// Scalar reference generators for each lane of a vector generator seeded through `from_scalar_seed`,
+/// where lane `i` is jumped `i` times.
+pub(crate) fn jumped_lanes<R: Clone>(mut rng: R, lanes: usize, jump: impl Fn(&mut R)) -> Vec<R> {
+    let mut result = Vec::with_capacity(lanes);
+    for _ in 0..lanes {
+        result.push(rng.clone());
+        jump(&mut rng);
+    }
+    result
+}
+
+/// Scalar generator with the given state. `rand_xoshiro` only exposes the state through serde,
+/// but `from_seed` reads it from the little endian words of the seed.
+pub(crate) fn scalar_from_state<R: SeedableRng<Seed = [u8; 32]>>(state: [u64; 4]) -> R {
+    let mut seed = [0u8; 32];
+    for (chunk, word) in seed.chunks_exact_mut(mem::size_of::<u64>()).zip(state) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+    R::from_seed(seed)
+}
+
+/// The first `len` values of the vectors returned by `next` in lane order, i.e. what the `fill_*` methods should write.
+pub(crate) fn vector_stream<T: Copy>(len: usize, mut next: impl FnMut() -> Vec<T>) -> Vec<T> {
+    let mut values = Vec::with_capacity(len);
+    while values.len() < len {
+        values.extend(next());
+    }
+    values.truncate(len);
+    values
+}
+
+/// Tests of the API [`crate::xoshiro::vector_xoshiro`] implements, checking every lane against the scalar generator.
+/// `portable` is a portable generator of the same variant with at least as many lanes,
+/// since lane `i` is the same regardless of lane count and implementation.
+macro_rules! xoshiro_tests {
+    ($rng:ty, seed: $seed:ty, scalar: $scalar:ty, lanes: $lanes:expr, portable: $portable:ty $(,)?) => {
+        mod shared {
+            use std::{array, mem};
+
+            use rand::{seq::SliceRandom, Rng};
+            use rand_core::{RngCore, SeedableRng};
+            use serial_test::parallel;
+
+            use crate::seeding::{split_seeds, SeedFromKey, SplittableRng};
+            use crate::testutil::{jumped_lanes, scalar_from_state};
+            use crate::xoshiro::JumpPolynomial;
+
+            use super::*;
+
+            const N: usize = $lanes;
+            type RngSeed = $seed;
+            type RngImpl = $rng;
+            type ScalarImpl = $scalar;
+            type Portable = $portable;
+
+            fn assert_same_lanes(rng: &mut RngImpl, lanes: &mut [ScalarImpl]) {
+                for _ in 0..100 {
+                    let values = rng.next_lanes();
+                    for (&v, lane) in values.iter().zip(lanes.iter_mut()) {
+                        assert_eq!(v, lane.next_u64());
+                    }
+                }
+            }
+
+            fn scalar_lanes<const M: usize>(states: [[u64; 4]; M]) -> Vec<ScalarImpl> {
+                states.into_iter().map(scalar_from_state).collect()
+            }
+
+            fn assert_same_streams(lanes: impl IntoIterator<Item = (ScalarImpl, ScalarImpl)>) {
+                for (mut lane, mut expected) in lanes {
+                    for _ in 0..100 {
+                        assert_eq!(lane.next_u64(), expected.next_u64());
+                    }
+                }
+            }
+
+            #[test]
+            #[parallel]
+            fn default_seed() {
+                let error = RngImpl::try_from_seed(RngSeed::default()).err().unwrap();
+                assert_eq!(error.lanes(), (0..N).collect::<Vec<_>>());
+
+                let mut rng = RngImpl::from_seed(RngSeed::default());
+                let mut lanes: [_; N] = array::from_fn(|i| ScalarImpl::seed_from_u64(i as u64));
+                assert_same_lanes(&mut rng, &mut lanes);
+            }
+
+            #[test]
+            #[parallel]
+            fn zero_lane() {
+                let mut seed: RngSeed = Default::default();
+                rand::thread_rng().fill_bytes(&mut *seed);
+                // Zeroing the first word of every state vector gives exactly one lane an all-zero state
+                for chunk in seed.chunks_mut(N * mem::size_of::<u64>()) {
+                    chunk[..mem::size_of::<u64>()].fill(0);
+                }
+                let seed = seed.to_vec();
+
+                let error = RngImpl::try_from_seed(seed.clone().into()).err().unwrap();
+                assert_eq!(error.lanes().len(), 1);
+
+                let lane = error.lanes()[0];
+                let repaired = scalar_lanes(RngImpl::from_seed(seed.into()).lane_states())[lane].clone();
+                assert_same_streams([(repaired, ScalarImpl::seed_from_u64(lane as u64))]);
+            }
+
+            #[test]
+            #[parallel]
+            fn from_key() {
+                let key = "run-2026-10/replicate-17";
+                let lanes = scalar_lanes(RngImpl::from_seed(RngSeed::from_str_key(key)).lane_states());
+
+                // Lanes are the same regardless of lane count and implementation
+                let other = scalar_lanes(Portable::from_seed(SeedFromKey::from_str_key(key)).lane_states());
+                assert_same_streams(lanes.clone().into_iter().zip(other));
+
+                let expected = ScalarImpl::from_seed(<[u8; 32]>::from_str_key(key));
+                assert_same_streams([(lanes[0].clone(), expected)]);
+
+                assert_ne!(
+                    *RngSeed::from_str_key(key),
+                    *RngSeed::from_str_key("run-2026-10/replicate-18")
+                );
+            }
+
+            #[test]
+            #[parallel]
+            fn from_scalar_seed() {
+                for seed in [rand::random(), [0; 32]] {
+                    let mut rng = RngImpl::from_scalar_seed(seed);
+                    let mut lanes = jumped_lanes(ScalarImpl::from_seed(seed), N, |rng| rng.jump());
+
+                    assert_same_lanes(&mut rng, &mut lanes);
+                }
+            }
+
+            #[test]
+            #[parallel]
+            fn lane_states() {
+                let mut states: [[u64; 4]; N] = array::from_fn(|_| rand::random());
+                states[0] = [0; 4];
+                let mut rng = RngImpl::from_lane_states(states);
+                let mut expected = scalar_lanes(states);
+                // The all-zero lane is repaired like in `from_seed`
+                expected[0] = ScalarImpl::seed_from_u64(0);
+                assert_same_lanes(&mut rng, &mut expected);
+
+                let states = rng.lane_states();
+                assert_eq!(RngImpl::from_lane_states(states).lane_states(), states);
+                assert_same_streams(scalar_lanes(states).into_iter().zip(expected));
+            }
+
+            #[test]
+            #[parallel]
+            #[cfg(feature = "rand_xoshiro")]
+            fn lanes() {
+                let seed = rand::random();
+                let mut expected = jumped_lanes(ScalarImpl::from_seed(seed), N, |rng| rng.jump());
+                let mut rng = RngImpl::from_lanes(expected.clone().try_into().unwrap());
+                assert_same_lanes(&mut rng, &mut expected);
+
+                let states = rng.lane_states();
+                assert_same_streams(rng.into_lanes().into_iter().zip(scalar_lanes(states)));
+            }
+
+            #[test]
+            #[parallel]
+            fn from_lane_seeds() {
+                let seeds: [u64; N] = array::from_fn(|_| rand::random());
+                let mut rng = RngImpl::from_lane_seeds(seeds);
+                let mut lanes = seeds.map(ScalarImpl::seed_from_u64);
+
+                assert_same_lanes(&mut rng, &mut lanes);
+            }
+
+            #[test]
+            #[parallel]
+            fn jump() {
+                let seed = rand::random();
+                let mut rng = RngImpl::from_scalar_seed(seed);
+                let mut lanes = jumped_lanes(ScalarImpl::from_seed(seed), N, |rng| rng.jump());
+                assert_same_lanes(&mut rng, &mut lanes);
+
+                rng.jump();
+                lanes.iter_mut().for_each(|lane| lane.jump());
+
+                assert_same_lanes(&mut rng, &mut lanes);
+            }
+
+            #[test]
+            #[parallel]
+            fn long_jump() {
+                let seed = rand::random();
+                let mut rng = RngImpl::from_scalar_seed(seed);
+                let mut lanes = jumped_lanes(ScalarImpl::from_seed(seed), N, |rng| rng.jump());
+                assert_same_lanes(&mut rng, &mut lanes);
+
+                rng.long_jump();
+                lanes.iter_mut().for_each(|lane| lane.long_jump());
+
+                assert_same_lanes(&mut rng, &mut lanes);
+            }
+
+            #[test]
+            #[parallel]
+            fn advance() {
+                let seed = rand::random();
+                for n in [0, 1, 1000] {
+                    let mut rng = RngImpl::from_scalar_seed(seed);
+                    let mut lanes = jumped_lanes(ScalarImpl::from_seed(seed), N, |rng| rng.jump());
+
+                    rng.advance(n);
+                    for lane in &mut lanes {
+                        for _ in 0..n {
+                            lane.next_u64();
+                        }
+                    }
+
+                    assert_same_lanes(&mut rng, &mut lanes);
+                }
+            }
+
+            #[test]
+            #[parallel]
+            fn advance_with() {
+                let seed = rand::random();
+                let mut rng = RngImpl::from_scalar_seed(seed);
+                let mut lanes = jumped_lanes(ScalarImpl::from_seed(seed), N, |rng| rng.jump());
+
+                let polynomial = JumpPolynomial::new(1 << 127);
+                rng.advance_with(&polynomial);
+                rng.advance_with(&polynomial);
+                lanes.iter_mut().for_each(|lane| lane.jump());
+
+                assert_same_lanes(&mut rng, &mut lanes);
+            }
+
+            #[test]
+            #[parallel]
+            fn from_seed_and_stream() {
+                let seed = rand::random();
+                for stream_id in 0..3 {
+                    let mut rng = RngImpl::from_seed_and_stream(seed, stream_id);
+                    let mut expected = RngImpl::from_scalar_seed(seed);
+                    for _ in 0..stream_id {
+                        expected.long_jump();
+                    }
+                    let mut lanes = scalar_lanes(expected.lane_states());
+                    assert_same_lanes(&mut rng, &mut lanes);
+                }
+
+                // Same streams as the portable implementation
+                let lanes = scalar_lanes(RngImpl::from_seed_and_stream(seed, 5).lane_states());
+                let portable = scalar_lanes(Portable::from_seed_and_stream(seed, 5).lane_states());
+                assert_same_streams(lanes.into_iter().zip(portable));
+            }
+
+            #[test]
+            #[parallel]
+            fn split() {
+                fn split_depth_first(rng: RngImpl, depth: u32, leaves: &mut Vec<RngImpl>) {
+                    if depth == 0 {
+                        leaves.push(rng);
+                        return;
+                    }
+                    let (left, right) = rng.split();
+                    split_depth_first(left, depth - 1, leaves);
+                    split_depth_first(right, depth - 1, leaves);
+                }
+
+                let seed = rand::random();
+                let mut depth_first = Vec::new();
+                split_depth_first(RngImpl::from_scalar_seed(seed), 3, &mut depth_first);
+
+                let mut breadth_first = vec![RngImpl::from_scalar_seed(seed)];
+                for _ in 0..3 {
+                    breadth_first = breadth_first
+                        .into_iter()
+                        .flat_map(|rng| {
+                            let (left, right) = rng.split();
+                            [left, right]
+                        })
+                        .collect();
+                }
+
+                for (i, (a, b)) in depth_first.into_iter().zip(breadth_first).enumerate() {
+                    // The path to leaf `i` is the bits of `i`, right halves at depth `d` adding 2^(127 - d) steps
+                    let mut expected = RngImpl::from_scalar_seed(seed);
+                    expected.advance((i as u128) << 125);
+
+                    let expected = scalar_lanes(expected.lane_states());
+                    assert_same_streams(scalar_lanes(a.lane_states()).into_iter().zip(expected.clone()));
+                    assert_same_streams(scalar_lanes(b.lane_states()).into_iter().zip(expected));
+                }
+            }
+
+            #[test]
+            #[parallel]
+            fn deep_split() {
+                let mut rng = RngImpl::from_scalar_seed(rand::random());
+                for _ in 0..128 {
+                    rng = rng.split().1;
+                }
+
+                // From depth 128 on the halves are rekeyed with a key drawn from the generator
+                let (left_seed, right_seed) = split_seeds(&mut RngImpl::from_lane_states(rng.lane_states()));
+                let (left, right) = rng.split();
+                assert_eq!(
+                    left.lane_states(),
+                    RngImpl::from_scalar_seed(left_seed).lane_states()
+                );
+                assert_eq!(
+                    right.lane_states(),
+                    RngImpl::from_scalar_seed(right_seed).lane_states()
+                );
+
+                // and start new split trees
+                let mut expected = RngImpl::from_scalar_seed(right_seed);
+                expected.advance(1 << 127);
+                assert_eq!(right.split().1.lane_states(), expected.lane_states());
+            }
+
+            #[test]
+            #[parallel]
+            fn rng_core() {
+                let seed = rand::random();
+                let mut rng = RngImpl::from_scalar_seed(seed);
+                let mut expected = RngImpl::from_scalar_seed(seed);
+
+                for _ in 0..10 {
+                    for v in expected.next_lanes() {
+                        assert_eq!(rng.next_u64(), v);
+                    }
+                }
+                for v in expected.next_lanes() {
+                    assert_eq!(rng.next_u32(), (v >> 32) as u32);
+                }
+
+                let mut bytes = [0u8; N * mem::size_of::<u64>() + 3];
+                rng.fill_bytes(&mut bytes);
+                let values = [expected.next_lanes(), expected.next_lanes()];
+                for (chunk, v) in bytes.chunks(mem::size_of::<u64>()).zip(values.as_flattened()) {
+                    assert_eq!(chunk, &v.to_le_bytes()[..chunk.len()]);
+                }
+
+                // Jumps discard the rest of the buffered vector
+                rng.jump();
+                expected.jump();
+                assert_eq!(rng.next_u64(), expected.next_lanes()[0]);
+            }
+
+            #[test]
+            #[parallel]
+            fn gen_range() {
+                let mut rng = RngImpl::seed_from_u64(0);
+                for _ in 0..1000 {
+                    assert!((10..20).contains(&rng.gen_range(10..20)));
+                    assert!((-1.0..1.0).contains(&rng.gen_range(-1.0..1.0)));
+                }
+
+                let mut values: Vec<u32> = (0..100).collect();
+                values.shuffle(&mut rng);
+                assert_ne!(values, (0..100).collect::<Vec<_>>());
+            }
+        }
+    };
+}
+
+pub(crate) use xoshiro_tests;
+
 pub(crate) const DOUBLE_RANGE: Range<f64> = 0.0..1.0;
 pub(crate) const FLOAT_RANGE: Range<f32> = 0.0f32..1.0f32;
 
@@ -0,0 +1,830 @@
+//! Helpers shared by the portable and specific xoshiro256 vector implementations,
+//! such as [`JumpPolynomial`] for advancing generators by arbitrary distances.
+//! xoshiro256+ and xoshiro256++ share the same state transition, so everything in here applies to both.
+
+use std::{array, fmt, sync::LazyLock};
+
+use rand_core::le::read_u64_into;
+#[cfg(feature = "rand_xoshiro")]
+use serde::{
+    de::{self, value::MapDeserializer, DeserializeOwned},
+    ser::{self, Impossible, SerializeStruct, SerializeTuple},
+    Serialize, Serializer,
+};
+
+use crate::seeding::{self, KeyDigest};
+
+/// Jump polynomial for advancing xoshiro256 generators by a fixed number of steps,
+/// i.e. `x^n mod P(x)` where `P` is the characteristic polynomial of the state transition.
+///
+/// Computing the polynomial for an arbitrary distance takes some work,
+/// so when advancing many generators by the same distance it should be computed once and reused,
+/// e.g. through `advance_with` on the vector generators.
+///
+/// ```
+/// use rand_core::SeedableRng;
+/// use simd_rand::portable::*;
+/// use simd_rand::xoshiro::JumpPolynomial;
+///
+/// let polynomial = JumpPolynomial::new(1_000_000);
+///
+/// let mut rngs: Vec<_> = (0..4u8).map(|i| Xoshiro256PlusPlusX8::from_scalar_seed([i + 1; 32])).collect();
+/// for rng in &mut rngs {
+///     rng.advance_with(&polynomial);
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct JumpPolynomial(pub(crate) [u64; 4]);
+
+impl JumpPolynomial {
+    /// 2^128 steps, from the reference implementation of `jump()`.
+    pub const JUMP: Self = Self([
+        0x180ec6d33cfd0aba,
+        0xd5a61266f0c9392c,
+        0xa9582618e03fc9aa,
+        0x39abdc4529b1661c,
+    ]);
+
+    /// 2^192 steps, from the reference implementation of `long_jump()`.
+    pub const LONG_JUMP: Self = Self([
+        0x76e15d3efefdcbbf,
+        0xc5004e441c522fb3,
+        0x77710069854ee241,
+        0x39109bb02acbe635,
+    ]);
+
+    /// Low 256 coefficients of the characteristic polynomial of the xoshiro256 state transition,
+    /// the coefficient of `x^256` is implicitly 1.
+    const CHARACTERISTIC: [u64; 4] = [
+        0x9d116f2bb0f0f001,
+        0x0280002bcefd1a5e,
+        0x04b4edcf26259f85,
+        0x0003c03c3f3ecb19,
+    ];
+
+    /// Computes the polynomial for advancing `n` steps, using square-and-multiply over GF(2).
+    pub fn new(n: u128) -> Self {
+        let mut result = Self([1, 0, 0, 0]);
+        for b in (0..u128::BITS - n.leading_zeros()).rev() {
+            result = result.square();
+            if (n >> b) & 1 != 0 {
+                result = result.mul_x();
+            }
+        }
+        result
+    }
+
+    /// Computes the polynomial for advancing `k` times the distance of `self`,
+    /// e.g. `JumpPolynomial::LONG_JUMP.pow(k)` for `k * 2^192` steps.
+    pub fn pow(self, k: u64) -> Self {
+        let mut result = Self([1, 0, 0, 0]);
+        for b in (0..u64::BITS - k.leading_zeros()).rev() {
+            result = result.square();
+            if (k >> b) & 1 != 0 {
+                result = result.mul(self);
+            }
+        }
+        result
+    }
+
+    /// Multiplies by `x`, reducing by the characteristic polynomial.
+    fn mul_x(self) -> Self {
+        let [w0, w1, w2, w3] = self.0;
+        let mut result = [
+            w0 << 1,
+            (w1 << 1) | (w0 >> 63),
+            (w2 << 1) | (w1 >> 63),
+            (w3 << 1) | (w2 >> 63),
+        ];
+        if w3 >> 63 != 0 {
+            for (r, c) in result.iter_mut().zip(Self::CHARACTERISTIC) {
+                *r ^= c;
+            }
+        }
+        Self(result)
+    }
+
+    /// Multiplies, reducing by the characteristic polynomial.
+    /// Horner's scheme over the bits of `self`, from the highest.
+    fn mul(self, other: Self) -> Self {
+        let mut result = Self([0; 4]);
+        for word in self.0.iter().rev() {
+            for b in (0..64).rev() {
+                result = result.mul_x();
+                if (word >> b) & 1 != 0 {
+                    for (r, o) in result.0.iter_mut().zip(other.0) {
+                        *r ^= o;
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    fn square(self) -> Self {
+        self.mul(self)
+    }
+}
+
+#[inline(always)]
+pub(crate) fn step(s: &mut [u64; 4]) {
+    let t = s[1] << 17;
+
+    s[2] ^= s[0];
+    s[3] ^= s[1];
+    s[1] ^= s[2];
+    s[0] ^= s[3];
+
+    s[2] ^= t;
+
+    s[3] = s[3].rotate_left(45);
+}
+
+/// Applies a jump polynomial to a scalar state, same as the reference `jump()`.
+pub(crate) fn jump(mut s: [u64; 4], polynomial: &JumpPolynomial) -> [u64; 4] {
+    let mut result = [0; 4];
+    for word in polynomial.0 {
+        for b in 0..64 {
+            if (word >> b) & 1 != 0 {
+                for (r, s) in result.iter_mut().zip(s) {
+                    *r ^= s;
+                }
+            }
+            step(&mut s);
+        }
+    }
+    result
+}
+
+/// Applies a jump polynomial to every lane of vector states, same as [`jump`] on each lane.
+/// Works on all lanes at once, masking instead of branching so the loops vectorize.
+pub(crate) fn jump_lanes<const N: usize>(mut s: [[u64; N]; 4], polynomial: &JumpPolynomial) -> [[u64; N]; 4] {
+    let mut result = [[0; N]; 4];
+    for word in polynomial.0 {
+        for b in 0..64 {
+            let mask = 0u64.wrapping_sub((word >> b) & 1);
+            for (r, s) in result.iter_mut().zip(&s) {
+                for (r, s) in r.iter_mut().zip(s) {
+                    *r ^= s & mask;
+                }
+            }
+            step_lanes(&mut s);
+        }
+    }
+    result
+}
+
+/// [`step`] on every lane of vector states.
+#[inline(always)]
+fn step_lanes<const N: usize>(s: &mut [[u64; N]; 4]) {
+    for i in 0..N {
+        let mut lane = [s[0][i], s[1][i], s[2][i], s[3][i]];
+        step(&mut lane);
+        for (words, word) in s.iter_mut().zip(lane) {
+            words[i] = word;
+        }
+    }
+}
+
+#[inline(always)]
+pub(crate) fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(seeding::GAMMA);
+    seeding::mix64(*state)
+}
+
+/// Expands a 64bit seed into a state using SplitMix64, same as `seed_from_u64` in `rand_xoshiro`.
+pub(crate) fn state_from_u64(seed: u64) -> [u64; 4] {
+    let mut x = seed;
+    [0; 4].map(|_| splitmix64(&mut x))
+}
+
+/// Reads a 256bit scalar seed into a state the same way `rand_xoshiro` does,
+/// including mapping the all-zero seed to the state `seed_from_u64(0)` would give.
+pub(crate) fn read_scalar_seed(seed: &[u8; 32]) -> [u64; 4] {
+    if seed.iter().all(|&b| b == 0) {
+        return state_from_u64(0);
+    }
+
+    let mut state = [0; 4];
+    read_u64_into(seed, &mut state);
+    state
+}
+
+/// Converts between lane states (`[[u64; 4]; N]`, one state per lane)
+/// and vector states (`[[u64; N]; 4]`, where `result[k][i]` is word `k` of the state of lane `i`).
+pub(crate) fn transpose<const R: usize, const C: usize>(m: [[u64; C]; R]) -> [[u64; R]; C] {
+    array::from_fn(|c| array::from_fn(|r| m[r][c]))
+}
+
+/// Vector states for a vector generator of `N` lanes seeded from a scalar seed.
+/// Lane `i` is the scalar state jumped `i` times, i.e. advanced by `i * 2^128` steps.
+pub(crate) fn lanes_from_scalar_seed<const N: usize>(seed: &[u8; 32]) -> [[u64; N]; 4] {
+    let mut state = read_scalar_seed(seed);
+    transpose(array::from_fn(|_| {
+        let lane = state;
+        state = jump(state, &JumpPolynomial::JUMP);
+        lane
+    }))
+}
+
+/// Vector states for a vector generator of `N` lanes, each lane seeded through [`state_from_u64`].
+pub(crate) fn lanes_from_u64_seeds<const N: usize>(seeds: [u64; N]) -> [[u64; N]; 4] {
+    transpose(seeds.map(state_from_u64))
+}
+
+/// `SPLIT_POLYNOMIALS[d]` advances `2^(127 - d)` steps, computed once by repeated squaring from `2^0` steps.
+static SPLIT_POLYNOMIALS: LazyLock<[JumpPolynomial; 128]> = LazyLock::new(|| {
+    let mut polynomials = [JumpPolynomial::new(1); 128];
+    for d in (0..127).rev() {
+        polynomials[d] = polynomials[d + 1].square();
+    }
+    polynomials
+});
+
+/// The jump polynomial for the right half of a split at `depth` splits from the root, i.e. `2^(127 - depth)` steps.
+/// `None` from depth 128 on, where the halves would be less than one step apart.
+pub(crate) fn split_polynomial(depth: u8) -> Option<&'static JumpPolynomial> {
+    SPLIT_POLYNOMIALS.get(depth as usize)
+}
+
+/// Vector states for a vector generator of `N` lanes seeded from a key,
+/// lane `i` getting block `i` of the key expansion described in [`crate::seeding`].
+pub(crate) fn lanes_from_key<const N: usize>(key: &[u8]) -> [[u64; N]; 4] {
+    let digest = KeyDigest::new(key);
+    transpose(array::from_fn(|i| digest.block(i as u64)))
+}
+
+/// Error from `try_from_seed` on the xoshiro vector generators,
+/// returned when the seed gives some lanes an all-zero state.
+/// The all-zero state is a fixed point of xoshiro, such a lane would output 0 forever.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZeroLanesError(Vec<usize>);
+
+impl ZeroLanesError {
+    /// Indices of the lanes with an all-zero state.
+    pub fn lanes(&self) -> &[usize] {
+        &self.0
+    }
+}
+
+impl fmt::Display for ZeroLanesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "The xoshiro seed gives an all-zero state to lanes {:?}", self.0)
+    }
+}
+
+impl std::error::Error for ZeroLanesError {}
+
+fn is_zero_lane<const N: usize>(states: &[[u64; N]; 4], lane: usize) -> bool {
+    states.iter().all(|words| words[lane] == 0)
+}
+
+pub(crate) fn check_zero_lanes<const N: usize>(states: &[[u64; N]; 4]) -> Result<(), ZeroLanesError> {
+    let lanes: Vec<_> = (0..N).filter(|&i| is_zero_lane(states, i)).collect();
+    if lanes.is_empty() {
+        Ok(())
+    } else {
+        Err(ZeroLanesError(lanes))
+    }
+}
+
+/// Replaces the all-zero state of lane `i` with [`state_from_u64`]`(i)`,
+/// similar to how `rand_xoshiro` replaces the all-zero seed with `seed_from_u64(0)`.
+pub(crate) fn repair_zero_lanes<const N: usize>(mut states: [[u64; N]; 4]) -> [[u64; N]; 4] {
+    for i in 0..N {
+        if is_zero_lane(&states, i) {
+            for (words, word) in states.iter_mut().zip(state_from_u64(i as u64)) {
+                words[i] = word;
+            }
+        }
+    }
+    states
+}
+
+/// Lanes of a vector drawn for `RngCore`, handed out in lane order.
+/// Lane `i` of a vector is returned before lane `i + 1`, and all of them before the next vector is drawn.
+#[derive(Clone, Copy)]
+pub(crate) struct LaneBuffer<const N: usize> {
+    lanes: [u64; N],
+    index: usize,
+}
+
+impl<const N: usize> LaneBuffer<N> {
+    pub(crate) const fn new() -> Self {
+        Self {
+            lanes: [0; N],
+            index: N,
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.index == N
+    }
+
+    #[inline(always)]
+    pub(crate) fn refill(&mut self, lanes: [u64; N]) {
+        self.lanes = lanes;
+        self.index = 0;
+    }
+
+    #[inline(always)]
+    pub(crate) fn pop(&mut self) -> u64 {
+        let value = self.lanes[self.index];
+        self.index += 1;
+        value
+    }
+}
+
+/// Access to the state of the scalar `rand_xoshiro` generators, which they only expose through serde.
+/// The derived impls of the `serde1` feature (de)serialize the generators as a struct with the state in field `s`.
+#[cfg(feature = "rand_xoshiro")]
+pub(crate) trait ScalarXoshiro: Serialize + DeserializeOwned {
+    fn into_state(self) -> [u64; 4] {
+        let mut words = Vec::with_capacity(4);
+        self.serialize(StateSerializer(&mut words))
+            .and_then(|()| words.try_into().map_err(|_| ser::Error::custom("expected 4 words")))
+            .unwrap_or_else(|e| panic!("Can't read the state of the scalar xoshiro generator: {e}"))
+    }
+
+    fn from_state(state: [u64; 4]) -> Self {
+        let fields = MapDeserializer::new([("s", state.to_vec())].into_iter());
+        Self::deserialize(fields).unwrap_or_else(|e: de::value::Error| {
+            panic!("Can't build the scalar xoshiro generator from its state: {e}")
+        })
+    }
+}
+
+#[cfg(feature = "rand_xoshiro")]
+impl ScalarXoshiro for rand_xoshiro::Xoshiro256PlusPlus {}
+
+#[cfg(feature = "rand_xoshiro")]
+impl ScalarXoshiro for rand_xoshiro::Xoshiro256Plus {}
+
+/// Serializer collecting the `u64`s of a struct of arrays, anything else is an error.
+#[cfg(feature = "rand_xoshiro")]
+struct StateSerializer<'a>(&'a mut Vec<u64>);
+
+#[cfg(feature = "rand_xoshiro")]
+macro_rules! unsupported {
+    ($($method:ident($($arg:ty),*) -> $ok:ty;)*) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> Result<$ok, Self::Error> {
+                Err(ser::Error::custom(concat!("unexpected ", stringify!($method))))
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "rand_xoshiro")]
+impl<'a> Serializer for StateSerializer<'a> {
+    type Ok = ();
+    type Error = de::value::Error;
+    type SerializeSeq = Impossible<(), Self::Error>;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Impossible<(), Self::Error>;
+    type SerializeTupleVariant = Impossible<(), Self::Error>;
+    type SerializeMap = Impossible<(), Self::Error>;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Impossible<(), Self::Error>;
+
+    fn serialize_u64(self, v: u64) -> Result<(), Self::Error> {
+        self.0.push(v);
+        Ok(())
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self, Self::Error> {
+        Ok(self)
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self, Self::Error> {
+        Ok(self)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, _: &T) -> Result<(), Self::Error> {
+        Err(ser::Error::custom("unexpected serialize_some"))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _: &'static str, _: &T) -> Result<(), Self::Error> {
+        Err(ser::Error::custom("unexpected serialize_newtype_struct"))
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<(), Self::Error> {
+        Err(ser::Error::custom("unexpected serialize_newtype_variant"))
+    }
+
+    unsupported! {
+        serialize_bool(bool) -> ();
+        serialize_i8(i8) -> ();
+        serialize_i16(i16) -> ();
+        serialize_i32(i32) -> ();
+        serialize_i64(i64) -> ();
+        serialize_u8(u8) -> ();
+        serialize_u16(u16) -> ();
+        serialize_u32(u32) -> ();
+        serialize_f32(f32) -> ();
+        serialize_f64(f64) -> ();
+        serialize_char(char) -> ();
+        serialize_str(&str) -> ();
+        serialize_bytes(&[u8]) -> ();
+        serialize_none() -> ();
+        serialize_unit() -> ();
+        serialize_unit_struct(&'static str) -> ();
+        serialize_unit_variant(&'static str, u32, &'static str) -> ();
+        serialize_seq(Option<usize>) -> Self::SerializeSeq;
+        serialize_tuple_struct(&'static str, usize) -> Self::SerializeTupleStruct;
+        serialize_tuple_variant(&'static str, u32, &'static str, usize) -> Self::SerializeTupleVariant;
+        serialize_map(Option<usize>) -> Self::SerializeMap;
+        serialize_struct_variant(&'static str, u32, &'static str, usize) -> Self::SerializeStructVariant;
+    }
+}
+
+#[cfg(feature = "rand_xoshiro")]
+impl<'a> SerializeTuple for StateSerializer<'a> {
+    type Ok = ();
+    type Error = de::value::Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        value.serialize(StateSerializer(self.0))
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "rand_xoshiro")]
+impl<'a> SerializeStruct for StateSerializer<'a> {
+    type Ok = ();
+    type Error = de::value::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, _: &'static str, value: &T) -> Result<(), Self::Error> {
+        value.serialize(StateSerializer(self.0))
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Implements the API shared by the xoshiro vector generators: seeding, jumps, splitting and [`rand_core::RngCore`].
+///
+/// The generator provides the primitives this builds on as private methods:
+/// `from_states` to construct it from vector states (see [`transpose`]) with `depth: 0` and an empty `buffer: LaneBuffer`,
+/// `states` to read them back, and `next_lanes` to draw a vector as an array of lanes.
+/// `read_seed_lanes` and `write_seed_lanes` convert between one vector of the seed and its lanes.
+macro_rules! vector_xoshiro {
+    (
+        impl<const $n:ident: usize> $rng:ty { $($body:tt)* }
+        where $($bound:tt)*
+    ) => {
+        $crate::xoshiro::vector_xoshiro! { @impl [$n] $rng { $($body)* } where $($bound)* }
+    };
+    (
+        impl $rng:ty { $($body:tt)* }
+    ) => {
+        $crate::xoshiro::vector_xoshiro! { @impl [] $rng { $($body)* } }
+    };
+    (
+        @impl [$($n:ident)?] $rng:ty {
+            seed: $seed:ty,
+            lanes: $lanes:expr,
+            scalar: $scalar:ident,
+            read_seed_lanes: $read:expr,
+            write_seed_lanes: $write:expr $(,)?
+        }
+        $(where $($bound:tt)*)?
+    ) => {
+        impl$(<const $n: usize>)? $crate::seeding::SeedFromKey for $seed $(where $($bound)*)? {
+            /// Lane `i` gets block `i` of the key expansion, see [`crate::seeding`].
+            fn from_key(key: &[u8]) -> Self {
+                let mut seed = <Self as Default>::default();
+                let vec_size = $lanes * std::mem::size_of::<u64>();
+                let vectors = $crate::xoshiro::lanes_from_key::<{ $lanes }>(key);
+                for (chunk, lanes) in seed.chunks_exact_mut(vec_size).zip(vectors) {
+                    ($write)(lanes, chunk);
+                }
+                seed
+            }
+        }
+
+        impl$(<const $n: usize>)? rand_core::SeedableRng for $rng $(where $($bound)*)? {
+            type Seed = $seed;
+
+            /// Repairs lanes the seed gives an all-zero state, see [`Self::try_from_seed`].
+            fn from_seed(seed: Self::Seed) -> Self {
+                Self::from_states($crate::xoshiro::repair_zero_lanes(Self::seed_states(&seed)))
+            }
+        }
+
+        impl$(<const $n: usize>)? $rng $(where $($bound)*)? {
+            /// Seeds the generator like [`rand_core::SeedableRng::from_seed`], but instead of repairing lanes
+            /// the seed gives an all-zero state, returns an error naming them.
+            /// The all-zero state is a fixed point of xoshiro, such a lane would output 0 forever.
+            pub fn try_from_seed(seed: $seed) -> Result<Self, $crate::xoshiro::ZeroLanesError> {
+                let states = Self::seed_states(&seed);
+                $crate::xoshiro::check_zero_lanes(&states)?;
+                Ok(Self::from_states(states))
+            }
+
+            /// Seeds all lanes from a single 256bit scalar seed.
+            /// Lane `i` starts at the scalar state advanced by `i * 2^128` steps using the xoshiro `jump()` polynomial,
+            /// so the lanes are guaranteed not to overlap.
+            #[doc = concat!("Lane 0 produces the same stream as `rand_xoshiro::", stringify!($scalar), "` seeded with the same seed.")]
+            pub fn from_scalar_seed(seed: [u8; 32]) -> Self {
+                Self::from_states($crate::xoshiro::lanes_from_scalar_seed(&seed))
+            }
+
+            /// Generator for stream `stream_id` of a 256bit scalar seed, e.g. one stream per Monte Carlo replicate.
+            /// Stream `k` is [`Self::from_scalar_seed`] advanced by `k * 2^192` steps, same as calling [`Self::long_jump`] `k` times.
+            /// So a stream only depends on the seed and its id, and streams never overlap
+            /// unless a lane draws more than 2^128 values.
+            ///
+            /// The scheme is the same for every xoshiro vector generator,
+            /// generators with the same number of lanes produce the same streams regardless of implementation.
+            pub fn from_seed_and_stream(seed: [u8; 32], stream_id: u64) -> Self {
+                let mut rng = Self::from_scalar_seed(seed);
+                rng.advance_with(&$crate::xoshiro::JumpPolynomial::LONG_JUMP.pow(stream_id));
+                rng
+            }
+
+            /// Builds a generator from the states of its lanes, lane `i` continuing the stream of a scalar
+            #[doc = concat!("`rand_xoshiro::", stringify!($scalar), "` with state `states[i]`.")]
+            /// Lanes with an all-zero state are repaired like in [`rand_core::SeedableRng::from_seed`].
+            pub fn from_lane_states(states: [[u64; 4]; $lanes]) -> Self {
+                Self::from_states($crate::xoshiro::repair_zero_lanes($crate::xoshiro::transpose(states)))
+            }
+
+            /// The states of the lanes, see [`Self::from_lane_states`].
+            pub fn lane_states(&self) -> [[u64; 4]; $lanes] {
+                $crate::xoshiro::transpose(self.states())
+            }
+
+            /// Builds a generator from scalar generators, lane `i` continuing the stream of `lanes[i]`.
+            #[cfg(feature = "rand_xoshiro")]
+            pub fn from_lanes(lanes: [rand_xoshiro::$scalar; $lanes]) -> Self {
+                Self::from_lane_states(lanes.map($crate::xoshiro::ScalarXoshiro::into_state))
+            }
+
+            /// Splits the generator into scalar generators, `result[i]` continuing the stream of lane `i`.
+            /// Useful for finishing ragged tails of a batch with the same streams.
+            #[cfg(feature = "rand_xoshiro")]
+            pub fn into_lanes(self) -> [rand_xoshiro::$scalar; $lanes] {
+                self.lane_states().map($crate::xoshiro::ScalarXoshiro::from_state)
+            }
+
+            /// Seeds lane `i` from `seeds[i]` expanded with SplitMix64,
+            #[doc = concat!("so that it produces the same stream as `rand_xoshiro::", stringify!($scalar), "::seed_from_u64(seeds[i])`.")]
+            pub fn from_lane_seeds(seeds: [u64; $lanes]) -> Self {
+                Self::from_states($crate::xoshiro::lanes_from_u64_seeds(seeds))
+            }
+
+            /// Advances every lane by 2^128 steps, same as calling `jump()` on the scalar generator of each lane.
+            /// Can be used to hand out 2^128 non-overlapping subsequences, e.g. one per worker thread.
+            pub fn jump(&mut self) {
+                self.advance_with(&$crate::xoshiro::JumpPolynomial::JUMP);
+            }
+
+            /// Advances every lane by 2^192 steps, same as calling `long_jump()` on the scalar generator of each lane.
+            /// Can be used to hand out 2^64 starting points, from each of which [`Self::jump`]
+            /// generates 2^64 non-overlapping subsequences.
+            pub fn long_jump(&mut self) {
+                self.advance_with(&$crate::xoshiro::JumpPolynomial::LONG_JUMP);
+            }
+
+            /// Advances every lane by `n` steps.
+            /// Computes the [`JumpPolynomial`](crate::xoshiro::JumpPolynomial) for `n` every call,
+            /// use [`Self::advance_with`] when advancing by the same distance repeatedly.
+            pub fn advance(&mut self, n: u128) {
+                self.advance_with(&$crate::xoshiro::JumpPolynomial::new(n));
+            }
+
+            /// Advances every lane by the distance of a precomputed [`JumpPolynomial`](crate::xoshiro::JumpPolynomial).
+            /// Like all jumps, discards the lanes buffered for [`RngCore`](rand_core::RngCore).
+            pub fn advance_with(&mut self, polynomial: &$crate::xoshiro::JumpPolynomial) {
+                let states = $crate::xoshiro::jump_lanes(self.states(), polynomial);
+                *self = Self {
+                    depth: self.depth,
+                    ..Self::from_states(states)
+                };
+            }
+
+            /// Vector states of the seed, the seed holding the 4 state vectors one after another.
+            fn seed_states(seed: &$seed) -> [[u64; $lanes]; 4] {
+                let vec_size = $lanes * std::mem::size_of::<u64>();
+                std::array::from_fn(|k| ($read)(&seed[k * vec_size..(k + 1) * vec_size]))
+            }
+        }
+
+        impl$(<const $n: usize>)? $crate::seeding::SplittableRng for $rng $(where $($bound)*)? {
+            /// Splits by jumping ahead: the left half continues the stream of every lane,
+            /// while every lane of the right half is advanced by `2^(127 - d)` steps, `d` being the number of splits leading to `self`.
+            /// So siblings at depth `d` are `2^(127 - d)` steps apart, and starting from lanes `2^128` steps apart,
+            /// e.g. from [`Self::from_scalar_seed`], no two generators of a split tree overlap
+            /// unless one draws more than `2^(127 - d)` values per lane.
+            ///
+            /// Jumping ahead works for 128 levels. A generator split that deep is rekeyed instead:
+            /// it draws a 32 byte key, and the halves are seeded by [`Self::from_scalar_seed`] with seeds derived from the key
+            /// (see [`crate::seeding`]), each starting a new split tree at depth 0.
+            fn split(mut self) -> (Self, Self) {
+                let Some(polynomial) = $crate::xoshiro::split_polynomial(self.depth) else {
+                    let (left, right) = $crate::seeding::split_seeds(&mut self);
+                    return (Self::from_scalar_seed(left), Self::from_scalar_seed(right));
+                };
+                let left = Self {
+                    depth: self.depth + 1,
+                    ..self
+                };
+                let mut right = Self { ..left };
+                right.advance_with(polynomial);
+                (left, right)
+            }
+        }
+
+        /// Scalar output of the vector generator, e.g. for `rand::Rng` and `rand_distr`.
+        /// `next_u64` returns the lanes of a vector in lane order before drawing the next one,
+        /// the vector methods don't touch the buffered lanes.
+        /// `next_u32` takes the upper 32 bits of `next_u64`, same as the scalar `rand_xoshiro` generators,
+        /// and `fill_bytes` writes the little-endian bytes of consecutive `next_u64` values.
+        impl$(<const $n: usize>)? rand_core::RngCore for $rng $(where $($bound)*)? {
+            #[inline(always)]
+            fn next_u32(&mut self) -> u32 {
+                (self.next_u64() >> 32) as u32
+            }
+
+            #[inline(always)]
+            fn next_u64(&mut self) -> u64 {
+                if self.buffer.is_empty() {
+                    let lanes = self.next_lanes();
+                    self.buffer.refill(lanes);
+                }
+                self.buffer.pop()
+            }
+
+            #[inline(always)]
+            fn fill_bytes(&mut self, dest: &mut [u8]) {
+                for chunk in dest.chunks_mut(std::mem::size_of::<u64>()) {
+                    chunk.copy_from_slice(&self.next_u64().to_le_bytes()[..chunk.len()]);
+                }
+            }
+
+            #[inline(always)]
+            fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+                self.fill_bytes(dest);
+                Ok(())
+            }
+        }
+    };
+}
+
+pub(crate) use vector_xoshiro;
+
+#[cfg(test)]
+mod tests {
+    use rand_core::{RngCore, SeedableRng};
+    use rand_xoshiro::Xoshiro256PlusPlus;
+    use serial_test::parallel;
+
+    use crate::testutil::scalar_from_state;
+
+    use super::*;
+
+    fn assert_same_stream(mut actual: Xoshiro256PlusPlus, mut expected: Xoshiro256PlusPlus) {
+        for _ in 0..10 {
+            assert_eq!(actual.next_u64(), expected.next_u64());
+        }
+    }
+
+    #[test]
+    #[parallel]
+    fn jump_same_as_rand_xoshiro() {
+        let seed: [u8; 32] = rand::random();
+        let mut expected = Xoshiro256PlusPlus::from_seed(seed);
+        let mut state = read_scalar_seed(&seed);
+
+        for _ in 0..10 {
+            expected.next_u64();
+            step(&mut state);
+        }
+        expected.jump();
+        state = jump(state, &JumpPolynomial::JUMP);
+
+        assert_same_stream(scalar_from_state(state), expected);
+    }
+
+    #[test]
+    #[parallel]
+    fn jump_polynomials() {
+        // 2^128 = (2^127)^2, and squaring 64 times takes us from 2^128 to 2^192
+        assert_eq!(JumpPolynomial::new(1 << 127).square(), JumpPolynomial::JUMP);
+
+        let mut polynomial = JumpPolynomial::JUMP;
+        for _ in 0..64 {
+            polynomial = polynomial.square();
+        }
+        assert_eq!(polynomial, JumpPolynomial::LONG_JUMP);
+    }
+
+    #[test]
+    #[parallel]
+    fn split_polynomials() {
+        for depth in [0, 1, 63, 64, 126, 127] {
+            assert_eq!(split_polynomial(depth), Some(&JumpPolynomial::new(1 << (127 - depth))));
+        }
+        assert_eq!(split_polynomial(128), None);
+    }
+
+    #[test]
+    #[parallel]
+    fn pow() {
+        assert_eq!(JumpPolynomial::new(1000).pow(0), JumpPolynomial::new(0));
+        assert_eq!(JumpPolynomial::new(1000).pow(37), JumpPolynomial::new(37000));
+        // 2^64 jumps of 2^128 steps are a long jump of 2^192 steps
+        assert_eq!(JumpPolynomial::JUMP.pow(1 << 63).square(), JumpPolynomial::LONG_JUMP);
+    }
+
+    #[test]
+    #[parallel]
+    fn advance_same_as_stepping() {
+        let seed: [u8; 32] = rand::random();
+        for n in [0, 1, 2, 63, 64, 255, 256, 257, 1000, 12345] {
+            let mut expected = read_scalar_seed(&seed);
+            for _ in 0..n {
+                step(&mut expected);
+            }
+            let state = jump(read_scalar_seed(&seed), &JumpPolynomial::new(n));
+            assert_eq!(state, expected, "n = {n}");
+        }
+    }
+
+    #[test]
+    #[parallel]
+    #[cfg(feature = "rand_xoshiro")]
+    fn scalar_state() {
+        let seed: [u8; 32] = rand::random();
+        let state = read_scalar_seed(&seed);
+
+        assert_eq!(ScalarXoshiro::into_state(Xoshiro256PlusPlus::from_seed(seed)), state);
+        assert_same_stream(ScalarXoshiro::from_state(state), Xoshiro256PlusPlus::from_seed(seed));
+    }
+
+    #[test]
+    #[parallel]
+    fn state_from_u64_same_as_rand_xoshiro() {
+        let seed = rand::random();
+        assert_same_stream(
+            scalar_from_state(state_from_u64(seed)),
+            Xoshiro256PlusPlus::seed_from_u64(seed),
+        );
+    }
+
+    #[test]
+    #[parallel]
+    fn zero_lanes() {
+        let mut states = lanes_from_u64_seeds::<4>(rand::random());
+        for words in &mut states {
+            words[1] = 0;
+            words[3] = 0;
+        }
+
+        let error = check_zero_lanes(&states).unwrap_err();
+        assert_eq!(error.lanes(), [1, 3]);
+        assert_eq!(
+            error.to_string(),
+            "The xoshiro seed gives an all-zero state to lanes [1, 3]"
+        );
+
+        let repaired = repair_zero_lanes(states);
+        assert!(check_zero_lanes(&repaired).is_ok());
+        assert_eq!(transpose(repaired)[0], transpose(states)[0]);
+        assert_eq!(transpose(repaired)[1], state_from_u64(1));
+        assert_eq!(transpose(repaired)[3], state_from_u64(3));
+    }
+
+    #[test]
+    #[parallel]
+    fn zero_seed_same_as_rand_xoshiro() {
+        let state = read_scalar_seed(&[0; 32]);
+        assert_same_stream(scalar_from_state(state), Xoshiro256PlusPlus::from_seed([0; 32]));
+    }
+
+    #[test]
+    #[parallel]
+    fn lanes_are_jumped() {
+        let seed: [u8; 32] = rand::random();
+        let lanes = lanes_from_scalar_seed::<4>(&seed);
+
+        let mut expected = Xoshiro256PlusPlus::from_seed(seed);
+        for i in 0..4 {
+            let state = [lanes[0][i], lanes[1][i], lanes[2][i], lanes[3][i]];
+            assert_same_stream(scalar_from_state(state), expected.clone());
+            expected.jump();
+        }
+    }
+}
@@ -25,4 +25,47 @@ mod shishua {
         let stats = dhat::HeapStats::get();
         dhat::assert_eq!(start_stats.curr_bytes, stats.curr_bytes);
     }
+
+    #[test]
+    #[serial]
+    fn deallocates_after_move_across_threads() {
+        let _profiler = dhat::Profiler::builder().testing().build();
+        let layout_size = Shishua::<DEFAULT_BUFFER_SIZE>::LAYOUT.size();
+
+        let start_stats = dhat::HeapStats::get();
+        {
+            // Moved into a thread and dropped there
+            let mut rng: Shishua<DEFAULT_BUFFER_SIZE> = Shishua::seed_from_u64(0);
+            rng.next_u64();
+            std::thread::scope(|s| {
+                s.spawn(move || {
+                    let mut rng = rng;
+                    rng.next_u64();
+                });
+            });
+        }
+        let stats = dhat::HeapStats::get();
+        dhat::assert_eq!(start_stats.curr_bytes, stats.curr_bytes);
+
+        let start_stats = dhat::HeapStats::get();
+        {
+            // Moved into a thread and back out again, dropped on this thread
+            let rng: Shishua<DEFAULT_BUFFER_SIZE> = Shishua::seed_from_u64(0);
+            let mut rng = std::thread::scope(|s| {
+                s.spawn(move || {
+                    let mut rng = rng;
+                    rng.next_u64();
+                    rng
+                })
+                .join()
+                .unwrap()
+            });
+            rng.next_u64();
+
+            let end_stats = dhat::HeapStats::get();
+            dhat::assert!(end_stats.curr_bytes >= start_stats.curr_bytes + layout_size);
+        }
+        let stats = dhat::HeapStats::get();
+        dhat::assert_eq!(start_stats.curr_bytes, stats.curr_bytes);
+    }
 }